@@ -1,76 +1,424 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::auth::generate_token;
-use crate::cli::{Cli, Forecast};
-use crate::geolocation::Location;
-use crate::weatherkit::{DataSet, Weather};
+use crate::auth::{diagnose, generate_token, Credentials};
+use crate::cache;
+use crate::cli::{Cli, Command, Forecast, Output};
+use crate::config::Config;
+use crate::geocoding::Gazetteer;
+use crate::geolocation::{
+    EnvironmentProvider, GpsdProvider, IpApiProvider, Location, LocationProvider, ManualProvider,
+    ProviderChain, ProviderKind,
+};
+use crate::i18n::{Language, Message};
+use crate::metrics::{self, SharedSnapshot, Snapshot};
+use crate::output::series::Series;
+use crate::output::{csv, graphite, ics, influx, json, ndjson};
+use crate::retry::RetryPolicy;
+use crate::units::Units;
+use crate::weatherkit::{DataSet, Weather, WeatherAlert};
+use crate::window::TimeWindow;
 use anyhow::{anyhow, Context};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use clap::Parser;
-use reqwest::{Client, Method};
+use console::style;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Method, Request, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::time::Duration;
 use thiserror::Error;
+use tokio::net::TcpListener;
 
+/// How long a response is cached for, in seconds, if none of its datasets has an expiry time,
+/// e.g. when only alerts were requested.
+const DEFAULT_CACHE_TTL: i64 = 600;
+
+const WEATHER_CACHE_DIR: &str = "weather";
+
+/// How long dataset availability is cached for, in days. Availability is looked up per
+/// country and one degree cell of latitude and longitude, and changes very rarely.
+const AVAILABILITY_CACHE_TTL: i64 = 7;
+
+const AVAILABILITY_CACHE_DIR: &str = "availability";
+
+/// The directory of the cache that alert details are cached in, until the alerts expire.
+const ALERT_CACHE_DIR: &str = "alerts";
+/// The number of bytes of an unexpected response body included in errors.
+const BODY_EXCERPT_LENGTH: usize = 200;
+/// The shortest time between refreshes of the metrics, in case WeatherKit's data has already
+/// expired by the time it's downloaded.
+const METRICS_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// How long to wait before refreshing the metrics again after a refresh failed.
+const METRICS_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// The ways a request to WeatherKit can fail. Each maps to a distinct exit code so that
+/// scripts can tell them apart.
 #[derive(Error, Debug)]
-enum WeatherkitRequestError {
+pub enum WeatherkitRequestError {
     #[error("The server is unable to process the request due to an invalid parameter value.")]
     BadRequest,
     #[error(
         "The request isn’t authorized or doesn’t include the correct authentication information."
     )]
     Unauthorized,
+    #[error("The request is authorized but not permitted, check the WeatherKit service is enabled for the key.")]
+    Forbidden,
     #[error("There’s no active alert for the specified unique identifier.")]
     NotFound,
+    #[error("The WeatherKit request quota has been exceeded{}.", retry_after_message(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("WeatherKit failed to handle the request with status {status}: {excerpt}")]
+    ServerError { status: u16, excerpt: String },
+    #[error("WeatherKit responded with unexpected status {status}: {excerpt}")]
+    UnexpectedStatus { status: u16, excerpt: String },
+    #[error("Could not decode the WeatherKit response at `{path}`.")]
+    Decode {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Could not reach WeatherKit.")]
+    Connection(#[source] reqwest::Error),
+    #[error("WeatherKit sent nothing for {} seconds.", .0.as_secs_f64())]
+    Timeout(Duration),
 }
 
-pub struct App {
+fn retry_after_message(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(retry_after) => format!(", retry after {} seconds", retry_after.as_secs()),
+        None => String::new(),
+    }
+}
+
+impl WeatherkitRequestError {
+    /// The exit code for the error. Codes 1 and 2 are left for other errors and for invalid
+    /// usage respectively.
+    pub const fn exit_code(&self) -> u8 {
+        match self {
+            Self::BadRequest => 3,
+            Self::Unauthorized => 4,
+            Self::Forbidden => 5,
+            Self::NotFound => 6,
+            Self::RateLimited { .. } => 7,
+            Self::ServerError { .. } => 8,
+            Self::UnexpectedStatus { .. } => 9,
+            Self::Decode { .. } => 10,
+            Self::Connection(_) => 11,
+            Self::Timeout(_) => 12,
+        }
+    }
+
+    /// Returns whether retrying the request might succeed, and if so after how long the server
+    /// asked us to wait, if at all.
+    fn transience(&self) -> Option<Option<Duration>> {
+        match self {
+            Self::RateLimited { retry_after } => Some(*retry_after),
+            Self::ServerError { .. } | Self::Timeout(_) => Some(None),
+            Self::Connection(e) if e.is_connect() || e.is_timeout() => Some(None),
+            _ => None,
+        }
+    }
+
+    async fn from_response(response: Response) -> Self {
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|x| x.to_str().ok())
+            .and_then(parse_retry_after);
+
+        match status {
+            400 => Self::BadRequest,
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            429 => Self::RateLimited { retry_after },
+            _ => {
+                let body = response.text().await.unwrap_or_default();
+                let excerpt = match body.char_indices().nth(BODY_EXCERPT_LENGTH) {
+                    Some((i, _)) => format!("{}...", &body[..i]),
+                    None => body,
+                };
+
+                if (500..600).contains(&status) {
+                    Self::ServerError { status, excerpt }
+                } else {
+                    Self::UnexpectedStatus { status, excerpt }
+                }
+            }
+        }
+    }
+}
+
+/// Parses a Retry-After header, which is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => DateTime::parse_from_rfc2822(value)
+            .ok()
+            .and_then(|x| (x.with_timezone(&Utc) - Utc::now()).to_std().ok()),
+    }
+}
+
+/// A raw WeatherKit weather or alert response persisted between runs.
+#[derive(Serialize, Deserialize, Debug)]
+struct CachedWeather {
+    /// The earliest expiry time of the data in the response.
+    expire_time: DateTime<Utc>,
+    response: String,
+}
+
+/// The datasets available in an area, persisted between runs.
+#[derive(Serialize, Deserialize, Debug)]
+struct CachedAvailability {
+    expire_time: DateTime<Utc>,
+    datasets: Vec<DataSet>,
+}
+
+/// Everything needed to send requests to WeatherKit and to present its responses, none of
+/// which depends on the location.
+pub struct Session {
     client: Client,
-    location: Location,
     auth_token: String,
+    base_url: String,
+    retry_policy: RetryPolicy,
+    read_timeout: Duration,
+    refresh: bool,
+    language: Language,
+    display_timezone: Tz,
+    units: Units,
+}
+
+pub struct App {
+    session: Session,
+    location: Location,
+    window: TimeWindow,
 }
 
 impl App {
     pub async fn run() -> anyhow::Result<()> {
         let cli = Cli::parse();
-        let app = Self::new().await?;
-        let datasets = app
-            .get_available_datasets()
-            .await
-            .context("Could not download datasets.")?;
-        let weather = app
-            .get_weather(&datasets)
-            .await
-            .context("Could not download weather data.")?;
+        let config = Config::load(cli.config.as_deref())?;
+
+        if let Some(Command::Auth { print_token }) = cli.command {
+            return Self::handle_auth(&cli, &config, print_token);
+        }
+
+        if let Some(Command::Alert { id }) = &cli.command {
+            return Session::without_location(&cli, &config)?
+                .handle_alert(id, cli.offline, cli.output)
+                .await;
+        }
+
+        if let (Some(Command::ServeMetrics { .. }), true) = (&cli.command, cli.offline) {
+            return Err(anyhow!(
+                "serve-metrics keeps downloading the weather, so it can't run with --offline."
+            ));
+        }
+
+        let app = Self::new(&cli, &config).await?;
+        if let Some(Command::ServeMetrics {
+            listen,
+            forecast_hours,
+        }) = &cli.command
+        {
+            let credentials = Credentials::load(&cli.credentials, &config.credentials)?;
+            let token_lifetime = Duration::from_secs(config.credentials.token_lifetime);
+            return app
+                .serve_metrics(*listen, *forecast_hours, credentials, token_lifetime)
+                .await;
+        }
+
+        let weather = if cli.offline {
+            app.get_cached_weather(cli.forecast)?
+        } else {
+            let datasets = app.get_available_datasets(cli.forecast.datasets()).await?;
+
+            // None of the datasets are available at the location, so let the forecast handler
+            // report that rather than making a pointless request
+            if datasets.is_empty() {
+                Weather::default()
+            } else {
+                app.get_weather(&datasets)
+                    .await
+                    .context("Could not download weather data.")?
+            }
+        };
+
+        match cli.output {
+            Output::Text => {}
+            Output::Json => {
+                return json::write(
+                    weather,
+                    cli.forecast,
+                    &app.location,
+                    app.session.display_timezone,
+                    &app.session.units,
+                    cli.hours,
+                )
+            }
+            Output::Csv | Output::Ndjson | Output::Influx | Output::Graphite => {
+                let series = Series::new(
+                    weather,
+                    cli.forecast,
+                    &cli.columns,
+                    &app.session.units,
+                    app.session.display_timezone,
+                    cli.hours,
+                )?;
+                return match cli.output {
+                    Output::Csv => csv::write(&series),
+                    Output::Ndjson => ndjson::write(&series),
+                    Output::Influx => influx::write(&series, &app.location),
+                    _ => graphite::write(&series, &app.location),
+                };
+            }
+            Output::Ics => {
+                return ics::write(
+                    &weather,
+                    cli.forecast,
+                    &app.location,
+                    &app.session.units,
+                    app.session.language.catalog(),
+                )
+            }
+        }
 
         match cli.forecast {
             Forecast::Current => app.handle_current_forecast(weather),
             Forecast::Weekly => app.handle_weekly_forecast(weather),
-            Forecast::Hourly => app.handle_hourly_forecast(weather),
+            Forecast::Hourly => app.handle_hourly_forecast(weather, cli.hours),
             Forecast::NextHour => app.handle_next_hour(weather),
-            Forecast::Alerts => app.handle_alerts(weather),
+            Forecast::Alerts => app.handle_alerts(weather, cli.details, cli.offline).await,
+            Forecast::Calendar => Err(anyhow!(
+                "The calendar can only be written as JSON or as an iCalendar feed, with -o ics."
+            )),
         }
     }
 
-    pub async fn new() -> anyhow::Result<Self> {
-        let client = Client::builder().build()?;
-        let location = Location::get_current_location(&client).await?;
-        let auth_token = generate_token()?;
+    pub async fn new(cli: &Cli, config: &Config) -> anyhow::Result<Self> {
+        let client = Session::client(config)?;
+        let location = Self::resolve_location(cli, config, &client).await?;
+        let window = TimeWindow::from_args(&cli.time, cli.forecast, location.tz()?)?;
+        let display_timezone = match cli.display_timezone {
+            Some(timezone) => timezone,
+            None => location.tz()?,
+        };
 
         Ok(Self {
-            client,
+            session: Session::new(cli, config, client, display_timezone)?,
             location,
-            auth_token,
+            window,
         })
     }
 
+    /// Picks the location to forecast, preferring explicit coordinates, then a place name, then
+    /// a saved location, and finally falling back to the configured location providers.
+    async fn resolve_location(
+        cli: &Cli,
+        config: &Config,
+        client: &Client,
+    ) -> anyhow::Result<Location> {
+        if let Some(location) = Location::from_args(&cli.location)? {
+            return Ok(location);
+        }
+
+        if let Some(place) = &cli.location.place {
+            let paths = [config.gazetteer.as_slice(), &cli.location.gazetteer].concat();
+            return Gazetteer::load(&paths)?.lookup(place);
+        }
+
+        if let Some(name) = &cli.location.saved_location {
+            return config.location(name);
+        }
+
+        let kinds = if cli.location.geolocation.is_empty() {
+            &config.geolocation.providers
+        } else {
+            &cli.location.geolocation
+        };
+
+        let mut providers: Vec<Box<dyn LocationProvider + Send + Sync>> = Vec::new();
+        // Geolocating the IP address would touch the network, which --offline promises not to
+        for kind in kinds
+            .iter()
+            .filter(|x| !cli.offline || **x != ProviderKind::IpApi)
+        {
+            providers.push(match kind {
+                ProviderKind::Static => Box::new(ManualProvider::new(config.default_location()?)),
+                ProviderKind::Env => Box::new(EnvironmentProvider),
+                ProviderKind::Gpsd => Box::new(GpsdProvider::new(
+                    config.geolocation.gpsd_address.clone(),
+                    Duration::from_secs(config.geolocation.gpsd_timeout),
+                    config.gazetteer.clone(),
+                )),
+                ProviderKind::IpApi => Box::new(IpApiProvider::new(
+                    client.clone(),
+                    config.geolocation.ip_api_url.clone(),
+                    config.network.read_timeout(cli.read_timeout),
+                )),
+            });
+        }
+
+        ProviderChain::new(providers).locate().await
+    }
+
+    fn handle_auth(cli: &Cli, config: &Config, print_token: bool) -> anyhow::Result<()> {
+        let credentials = Credentials::load(&cli.credentials, &config.credentials)?;
+        let diagnostics = diagnose(
+            &credentials,
+            Duration::from_secs(config.credentials.token_lifetime),
+        )?;
+
+        println!("{}\n{}\n", style("Header").bold(), diagnostics.header);
+        println!("{}\n{}\n", style("Claims").bold(), diagnostics.claims);
+
+        let mut failures = 0;
+        for check in &diagnostics.checks {
+            match &check.result {
+                Ok(message) => println!("{} {}: {}", style("✓").green(), check.name, message),
+                Err(message) => {
+                    failures += 1;
+                    println!("{} {}: {}", style("✗").red(), check.name, message)
+                }
+            }
+        }
+
+        if let (true, Some(token)) = (print_token, &diagnostics.token) {
+            println!("\n{}", token);
+        }
+
+        match failures {
+            0 => Ok(()),
+            n => Err(anyhow!("{} credential check(s) failed.", n)),
+        }
+    }
+
     fn handle_current_forecast(&self, weather: Weather) -> anyhow::Result<()> {
         match weather.current_weather {
             Some(cw) => {
-                if let Some(daily_forecast) = &weather.forecast_daily {
-                    let todays_forecast = &daily_forecast.days[0];
+                // The day the current weather is for, or failing that the first of the forecast
+                let todays_forecast = weather.forecast_daily.as_ref().and_then(|x| {
+                    x.days
+                        .iter()
+                        .find(|day| day.forecast_start <= cw.as_of && cw.as_of < day.forecast_end)
+                        .or_else(|| x.days.first())
+                });
 
+                if let Some(todays_forecast) = todays_forecast {
                     if let (Some(sunrise), Some(sunset)) =
                         (todays_forecast.sunrise, todays_forecast.sunset)
                     {
-                        cw.prepare(&self.location, &sunrise, &sunset)?.render();
+                        cw.prepare(
+                            &self.location,
+                            &sunrise,
+                            &sunset,
+                            self.session.display_timezone,
+                            &self.session.units,
+                            self.session.language.catalog(),
+                        )?
+                        .render();
                         Ok(())
                     } else {
                         Err(anyhow!(
@@ -95,7 +443,12 @@ impl App {
     fn handle_weekly_forecast(&self, weather: Weather) -> anyhow::Result<()> {
         match weather.forecast_daily {
             Some(fd) => {
-                fd.prepare()?.render();
+                fd.prepare(
+                    self.location.tz()?,
+                    &self.session.units,
+                    self.session.language.catalog(),
+                )?
+                .render();
                 Ok(())
             }
             None => Err(anyhow!(
@@ -105,10 +458,17 @@ impl App {
         }
     }
 
-    fn handle_hourly_forecast(&self, weather: Weather) -> anyhow::Result<()> {
+    fn handle_hourly_forecast(&self, weather: Weather, hours: Option<usize>) -> anyhow::Result<()> {
         match weather.forecast_hourly {
             Some(fh) => {
-                fh.prepare(&self.location).render();
+                fh.prepare(
+                    &self.location,
+                    self.session.display_timezone,
+                    hours,
+                    &self.session.units,
+                    self.session.language.catalog(),
+                )?
+                .render();
                 Ok(())
             }
             None => Err(anyhow!(
@@ -121,7 +481,13 @@ impl App {
     fn handle_next_hour(&self, weather: Weather) -> anyhow::Result<()> {
         match weather.forecast_next_hour {
             Some(fnh) => {
-                fnh.prepare(&self.location).render();
+                fnh.prepare(
+                    &self.location,
+                    self.session.display_timezone,
+                    &self.session.units,
+                    self.session.language.catalog(),
+                )?
+                .render();
                 Ok(())
             }
             None => Err(anyhow!(
@@ -131,67 +497,670 @@ impl App {
         }
     }
 
-    fn handle_alerts(&self, weather: Weather) -> anyhow::Result<()> {
+    async fn handle_alerts(
+        &self,
+        weather: Weather,
+        details: bool,
+        offline: bool,
+    ) -> anyhow::Result<()> {
         match weather.weather_alerts {
-            Some(alerts) => alerts.prepare(&self.location).render(),
-            None => println!("No weather alerts at {}", self.location),
+            Some(alerts) => {
+                let ids = alerts
+                    .alerts
+                    .iter()
+                    .map(|x| x.id.clone())
+                    .collect::<Vec<_>>();
+                alerts
+                    .prepare(
+                        &self.location,
+                        self.session.display_timezone,
+                        self.session.language.catalog(),
+                    )
+                    .render();
+
+                if details {
+                    for id in &ids {
+                        self.session.handle_alert(id, offline, Output::Text).await?;
+                    }
+                }
+            }
+            None => println!(
+                "{} {}",
+                self.session.language.catalog().get(Message::NoAlerts),
+                self.location
+            ),
         }
 
         Ok(())
     }
 
-    async fn get_available_datasets(&self) -> anyhow::Result<Vec<DataSet>> {
-        let availability_url = self.location.get_availability_url();
+    /// Serves the weather as metrics until the server fails, refreshing it whenever it expires.
+    async fn serve_metrics(
+        mut self,
+        listen: SocketAddr,
+        forecast_hours: usize,
+        credentials: Credentials,
+        token_lifetime: Duration,
+    ) -> anyhow::Result<()> {
+        let listener = TcpListener::bind(listen)
+            .await
+            .with_context(|| format!("Could not listen on {}", listen))?;
+        let snapshot = SharedSnapshot::default();
+        let server = metrics::serve(
+            listener,
+            snapshot.clone(),
+            self.location.clone(),
+            forecast_hours,
+        );
+
+        let refresh = async {
+            loop {
+                let delay = match self
+                    .refresh_metrics(&snapshot, &credentials, token_lifetime)
+                    .await
+                {
+                    Ok(expire_time) => (expire_time - Utc::now())
+                        .to_std()
+                        .unwrap_or_default()
+                        .max(METRICS_MIN_REFRESH_INTERVAL),
+                    Err(e) => {
+                        // Keep serving the last weather rather than giving up on a transient failure
+                        eprintln!("Error: {:?}", e);
+                        METRICS_RETRY_DELAY
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        };
+
+        tokio::select! {
+            result = server => result,
+            _ = refresh => Ok(()),
+        }
+    }
+
+    /// Downloads the weather the metrics are made of, or reads it from the cache if it hasn't
+    /// expired yet, and returns when it expires.
+    async fn refresh_metrics(
+        &mut self,
+        snapshot: &SharedSnapshot,
+        credentials: &Credentials,
+        token_lifetime: Duration,
+    ) -> anyhow::Result<DateTime<Utc>> {
+        // The server runs for longer than tokens are valid for
+        self.session.auth_token = generate_token(credentials, token_lifetime)?;
+
+        let datasets = self
+            .get_available_datasets(&[DataSet::CurrentWeather, DataSet::ForecastHourly])
+            .await?;
+        let weather = if datasets.is_empty() {
+            Weather::default()
+        } else {
+            self.get_weather(&datasets)
+                .await
+                .context("Could not download weather data.")?
+        };
+        let expire_time = weather.expire_time().unwrap_or_else(Utc::now);
+
+        *snapshot
+            .lock()
+            .map_err(|_| anyhow!("The metrics snapshot is poisoned"))? = Snapshot {
+            weather,
+            refreshed: Some(Utc::now()),
+        };
+
+        Ok(expire_time)
+    }
+
+    /// The prefix of the names of cached responses for the location and time window, shared by
+    /// all combinations of datasets.
+    fn weather_cache_prefix(&self) -> String {
+        format!(
+            "{}/{:.2}_{:.2}_{}_{}_{}_",
+            WEATHER_CACHE_DIR,
+            self.location.lat,
+            self.location.lon,
+            self.session.language,
+            self.location.timezone.replace('/', "-"),
+            self.window.cache_name()
+        )
+    }
+
+    fn weather_cache_name(&self, datasets: &[DataSet]) -> String {
+        let datasets = datasets.iter().map(|x| x.fmt()).collect::<Vec<_>>();
+        format!("{}{}.json", self.weather_cache_prefix(), datasets.join("+"))
+    }
+
+    /// Returns the most recently expiring cached response for the location that includes the
+    /// datasets the forecast needs, regardless of whether it has expired.
+    fn get_cached_weather(&self, forecast: Forecast) -> anyhow::Result<Weather> {
+        let prefix = self.weather_cache_prefix();
+        let cached = cache::list(WEATHER_CACHE_DIR)
+            .into_iter()
+            .filter(|name| {
+                name.strip_prefix(&prefix)
+                    .and_then(|x| x.strip_suffix(".json"))
+                    .is_some_and(|x| {
+                        let cached = x.split('+').collect::<Vec<_>>();
+                        forecast
+                            .datasets()
+                            .iter()
+                            .all(|x| cached.contains(&x.fmt()))
+                    })
+            })
+            .filter_map(|name| cache::read::<CachedWeather>(&name))
+            .max_by_key(|x| x.expire_time)
+            .ok_or_else(|| {
+                anyhow!(
+                    "There is no cached weather data for location {}, run wetter without \
+                     --offline first.",
+                    self.location
+                )
+            })?;
+
+        Ok(decode(cached.response.as_bytes())?)
+    }
+
+    /// Returns those of the given datasets that are available at the location. Availability is
+    /// read from the cache if it was looked up for the same country and coordinate cell
+    /// recently. If WeatherKit can't be reached or fails to look it up, all of them are assumed
+    /// to be available.
+    async fn get_available_datasets(&self, wanted: &[DataSet]) -> anyhow::Result<Vec<DataSet>> {
+        let available = self.get_availability().await?;
+        Ok(wanted
+            .iter()
+            .copied()
+            .filter(|x| available.as_ref().is_none_or(|y| y.contains(x)))
+            .collect())
+    }
+
+    /// Returns the datasets available at the location, or `None` if WeatherKit can't be reached
+    /// or fails to look them up.
+    async fn get_availability(&self) -> anyhow::Result<Option<Vec<DataSet>>> {
+        let cache_name = format!(
+            "{}/{}_{}_{}.json",
+            AVAILABILITY_CACHE_DIR,
+            self.location.country_code,
+            self.location.lat.floor(),
+            self.location.lon.floor()
+        );
+
+        if !self.session.refresh {
+            if let Some(cached) = cache::read::<CachedAvailability>(&cache_name) {
+                if cached.expire_time > Utc::now() {
+                    return Ok(Some(cached.datasets));
+                }
+            }
+        }
+
+        match self.download_available_datasets().await {
+            Ok(datasets) => {
+                cache::write_best_effort(
+                    &cache_name,
+                    &CachedAvailability {
+                        expire_time: Utc::now() + chrono::Duration::days(AVAILABILITY_CACHE_TTL),
+                        datasets: datasets.clone(),
+                    },
+                );
+                Ok(Some(datasets))
+            }
+            // Other failures, e.g. of authentication, would fail the weather request as well
+            Err(e) => match e.downcast_ref::<WeatherkitRequestError>() {
+                Some(
+                    WeatherkitRequestError::Connection(_)
+                    | WeatherkitRequestError::ServerError { .. },
+                ) => Ok(None),
+                _ => Err(e.context("Could not look up the datasets available at the location.")),
+            },
+        }
+    }
+
+    async fn download_available_datasets(&self) -> anyhow::Result<Vec<DataSet>> {
+        let availability_url = self.location.get_availability_url(&self.session.base_url);
         let request = self
+            .session
             .client
             .request(Method::GET, availability_url)
             .query(&[("country", &self.location.country_code)])
-            .bearer_auth(&self.auth_token)
+            .bearer_auth(&self.session.auth_token)
             .build()
             .context("Failed to build request")?;
 
-        let response = self
-            .client
-            .execute(request)
-            .await
-            .context("Failed to execute request")?;
-
-        match response.status().as_u16() {
-            200 => Ok(response.json::<Vec<DataSet>>().await?),
-            400 => Err(WeatherkitRequestError::BadRequest.into()),
-            401 => Err(WeatherkitRequestError::Unauthorized.into()),
-            _ => unreachable!(),
-        }
+        self.session.execute(request).await
     }
 
+    /// Returns the weather for the given datasets from the cache if it hasn't expired yet, and
+    /// otherwise downloads and caches it.
     async fn get_weather(&self, datasets: &[DataSet]) -> anyhow::Result<Weather> {
-        let weather_url = self.location.get_weather_url();
+        let cache_name = self.weather_cache_name(datasets);
+        if !self.session.refresh {
+            if let Some(cached) = cache::read::<CachedWeather>(&cache_name) {
+                if cached.expire_time > Utc::now() {
+                    if let Ok(weather) = decode(cached.response.as_bytes()) {
+                        return Ok(weather);
+                    }
+                }
+            }
+        }
+
+        let weather_url = self
+            .location
+            .get_weather_url(&self.session.base_url, &self.session.language);
         let mut queries = Vec::from([
-            ("countryCode", self.location.country_code.as_str()),
-            ("timezone", self.location.timezone.as_str()),
+            ("countryCode", self.location.country_code.clone()),
+            ("timezone", self.location.timezone.clone()),
         ]);
-        queries.reserve(datasets.len());
-        queries.extend(datasets.iter().map(|x| ("dataSets", x.fmt())));
+        queries.extend(datasets.iter().map(|x| ("dataSets", x.fmt().to_owned())));
+        queries.extend(self.window.queries());
 
         let request = self
+            .session
             .client
             .request(Method::GET, weather_url)
             .query(&queries)
-            .bearer_auth(&self.auth_token)
+            .bearer_auth(&self.session.auth_token)
             .build()
             .context("Failed to build request")?;
 
-        let response = self
-            .client
-            .execute(request)
+        let body = self.session.execute_raw(request).await?;
+        let weather = decode::<Weather>(&body)?;
+
+        cache::write_best_effort(
+            &cache_name,
+            &CachedWeather {
+                expire_time: weather
+                    .expire_time()
+                    .unwrap_or_else(|| Utc::now() + chrono::Duration::seconds(DEFAULT_CACHE_TTL)),
+                response: String::from_utf8_lossy(&body).into_owned(),
+            },
+        );
+
+        Ok(weather)
+    }
+}
+
+impl Session {
+    fn client(config: &Config) -> anyhow::Result<Client> {
+        let network = &config.network;
+
+        Ok(Client::builder()
+            .connect_timeout(Duration::from_secs(network.connect_timeout))
+            .build()?)
+    }
+
+    fn new(
+        cli: &Cli,
+        config: &Config,
+        client: Client,
+        display_timezone: Tz,
+    ) -> anyhow::Result<Self> {
+        let network = &config.network;
+        // Nothing is sent when offline, so there's no need for credentials either
+        let auth_token = if cli.offline {
+            String::new()
+        } else {
+            let credentials = Credentials::load(&cli.credentials, &config.credentials)?;
+            generate_token(
+                &credentials,
+                Duration::from_secs(config.credentials.token_lifetime),
+            )?
+        };
+
+        Ok(Self {
+            client,
+            auth_token,
+            base_url: network.api_base_url.trim_end_matches('/').to_owned(),
+            retry_policy: network.retry_policy(cli.retries)?,
+            read_timeout: network.read_timeout(cli.read_timeout),
+            refresh: cli.refresh,
+            language: cli.lang.clone().unwrap_or_else(Language::from_env),
+            display_timezone,
+            units: Units::resolve(&cli.units, &config.units),
+        })
+    }
+
+    /// Sets up a session for commands that don't need a location, such as looking up an
+    /// alert by its ID.
+    fn without_location(cli: &Cli, config: &Config) -> anyhow::Result<Self> {
+        let display_timezone = match cli.display_timezone {
+            Some(timezone) => timezone,
+            None => Self::configured_timezone(cli, config)?.unwrap_or(Tz::UTC),
+        };
+
+        Self::new(cli, config, Self::client(config)?, display_timezone)
+    }
+
+    /// The timezone of a location given on the command line or saved in the config file, if
+    /// any. Unlike resolving the location, this never touches the network.
+    fn configured_timezone(cli: &Cli, config: &Config) -> anyhow::Result<Option<Tz>> {
+        let location = match (&cli.location.timezone, &cli.location.saved_location) {
+            (Some(timezone), _) => {
+                return timezone
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| anyhow!("\"{}\" is not a known IANA timezone", timezone))
+            }
+            (None, Some(name)) => Some(config.location(name)?),
+            (None, None) => config.default_location()?,
+        };
+
+        location.map(|x| x.tz()).transpose()
+    }
+
+    async fn handle_alert(&self, id: &str, offline: bool, output: Output) -> anyhow::Result<()> {
+        let alert = self
+            .get_alert(id, offline)
+            .await
+            .with_context(|| format!("Could not get the details of alert {}.", id))?;
+
+        match output {
+            Output::Text => {}
+            Output::Json => return json::write_alert(&alert, self.display_timezone, &self.units),
+            Output::Csv | Output::Ndjson | Output::Influx | Output::Graphite | Output::Ics => {
+                return Err(anyhow!("Alerts can only be written as text or JSON."))
+            }
+        }
+
+        alert
+            .prepare(self.display_timezone, &self.language)
+            .render();
+
+        Ok(())
+    }
+
+    /// Sends a request to WeatherKit and decodes the response, retrying transient failures
+    /// according to the retry policy.
+    async fn execute<T: DeserializeOwned>(&self, request: Request) -> anyhow::Result<T> {
+        let body = self.execute_raw(request).await?;
+        Ok(decode(&body)?)
+    }
+
+    /// Sends a request to WeatherKit and returns the body of the response, retrying transient
+    /// failures according to the retry policy.
+    async fn execute_raw(&self, request: Request) -> anyhow::Result<Vec<u8>> {
+        let mut attempts = 0;
+
+        loop {
+            let attempt = request
+                .try_clone()
+                .expect("GET requests have no body so can always be cloned");
+            attempts += 1;
+
+            match self.execute_once(attempt).await {
+                Err(e) => match e
+                    .transience()
+                    .and_then(|requested| self.retry_policy.delay(attempts, requested))
+                {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e.into()),
+                },
+                Ok(body) => return Ok(body),
+            }
+        }
+    }
+
+    /// Sends a request to WeatherKit once, mapping every failure to a
+    /// [`WeatherkitRequestError`].
+    async fn execute_once(&self, request: Request) -> Result<Vec<u8>, WeatherkitRequestError> {
+        let timed_out = |_| WeatherkitRequestError::Timeout(self.read_timeout);
+
+        let mut response = tokio::time::timeout(self.read_timeout, self.client.execute(request))
+            .await
+            .map_err(timed_out)?
+            .map_err(WeatherkitRequestError::Connection)?;
+
+        if response.status() != StatusCode::OK {
+            return Err(WeatherkitRequestError::from_response(response).await);
+        }
+
+        // Each part of the body has to arrive in time rather than all of it, so that a slow but
+        // steady download isn't cut off
+        let mut body = Vec::new();
+        while let Some(chunk) = tokio::time::timeout(self.read_timeout, response.chunk())
             .await
-            .context("Failed to execute request")?;
+            .map_err(timed_out)?
+            .map_err(WeatherkitRequestError::Connection)?
+        {
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+
+    /// Gets the full details of an alert, from the cache if they haven't expired yet.
+    async fn get_alert(&self, id: &str, offline: bool) -> anyhow::Result<WeatherAlert> {
+        // The ID is put in the path of the request and of the cache entry
+        if id.is_empty() || !id.chars().all(|x| x.is_ascii_alphanumeric() || x == '-') {
+            return Err(anyhow!("\"{}\" is not a valid alert ID.", id));
+        }
+
+        let cache_name = format!("{}/{}_{}.json", ALERT_CACHE_DIR, id, self.language);
+        if !self.refresh {
+            if let Some(cached) = cache::read::<CachedWeather>(&cache_name) {
+                if offline || cached.expire_time > Utc::now() {
+                    if let Ok(alert) = decode(cached.response.as_bytes()) {
+                        return Ok(alert);
+                    }
+                }
+            }
+        }
 
-        match response.status().as_u16() {
-            200 => Ok(response.json::<Weather>().await?),
-            400 => Err(WeatherkitRequestError::BadRequest.into()),
-            401 => Err(WeatherkitRequestError::Unauthorized.into()),
-            _ => unreachable!(),
+        if offline {
+            return Err(anyhow!(
+                "The alert isn't cached, run wetter without --offline first."
+            ));
         }
+
+        let request = self
+            .client
+            .request(
+                Method::GET,
+                format!("{}/weatherAlert/{}/{}", self.base_url, self.language, id),
+            )
+            .bearer_auth(&self.auth_token)
+            .build()
+            .context("Failed to build request")?;
+
+        let body = self.execute_raw(request).await.map_err(|e| {
+            // Only here does a 404 mean something specific, that the alert doesn't exist
+            match e.downcast::<WeatherkitRequestError>() {
+                Ok(WeatherkitRequestError::UnexpectedStatus { status: 404, .. }) => {
+                    WeatherkitRequestError::NotFound.into()
+                }
+                Ok(e) => e.into(),
+                Err(e) => e,
+            }
+        })?;
+        let alert = decode::<WeatherAlert>(&body)?;
+
+        cache::write_best_effort(
+            &cache_name,
+            &CachedWeather {
+                expire_time: alert.summary.expire_time,
+                response: String::from_utf8_lossy(&body).into_owned(),
+            },
+        );
+
+        Ok(alert)
+    }
+}
+
+/// Decodes a WeatherKit response, reporting where in the response decoding failed.
+fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, WeatherkitRequestError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+
+    serde_path_to_error::deserialize(deserializer).map_err(|e| WeatherkitRequestError::Decode {
+        path: e.path().to_string(),
+        source: e.into_inner(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::UnitSystem;
+    use std::io::{Read, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serves the given statuses in turn on a local port, repeating the last one, and returns
+    /// the base URL of the server and the number of requests it has received.
+    fn serve(statuses: &'static [&'static str]) -> (String, Arc<AtomicUsize>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                read_request(&mut stream);
+
+                let index = counter.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[index.min(statuses.len() - 1)];
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}",
+                    status
+                );
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    /// Serves a body in the given parts, each after the given delay, and returns the base URL
+    /// of the server.
+    fn serve_slowly(parts: &'static [&'static str], delay: Duration) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                read_request(&mut stream);
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n"
+                );
+                for part in parts {
+                    std::thread::sleep(delay);
+                    let _ = write!(stream, "{:x}\r\n{}\r\n", part.len(), part);
+                }
+                let _ = write!(stream, "0\r\n\r\n");
+            }
+        });
+
+        base_url
+    }
+
+    fn read_request(stream: &mut std::net::TcpStream) {
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|x| x == b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+    }
+
+    fn session(base_url: String) -> Session {
+        Session {
+            client: Client::new(),
+            auth_token: String::new(),
+            base_url,
+            retry_policy: RetryPolicy {
+                retries: 2,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(10),
+            },
+            read_timeout: Duration::from_millis(100),
+            refresh: true,
+            language: Language::default(),
+            display_timezone: Tz::UTC,
+            units: Units::from(UnitSystem::Metric),
+        }
+    }
+
+    async fn get(session: &Session) -> anyhow::Result<Vec<u8>> {
+        let request = session
+            .client
+            .get(format!("{}/availability", session.base_url))
+            .build()?;
+        session.execute_raw(request).await
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors() {
+        let (base_url, requests) = serve(&["503 Service Unavailable", "200 OK"]);
+
+        let body = get(&session(base_url)).await.unwrap();
+
+        assert_eq!(body, b"{}");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_retries() {
+        let (base_url, requests) = serve(&["503 Service Unavailable"]);
+
+        let error = get(&session(base_url)).await.unwrap_err();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            error
+                .downcast_ref::<WeatherkitRequestError>()
+                .map(WeatherkitRequestError::exit_code),
+            Some(8)
+        );
+    }
+
+    #[tokio::test]
+    async fn waits_for_a_slow_but_steady_response() {
+        // Longer than the read timeout in all, but never for longer than it at once
+        let base_url = serve_slowly(&["[", "1", ",2", "]"], Duration::from_millis(40));
+
+        let body = get(&session(base_url)).await.unwrap();
+
+        assert_eq!(body, b"[1,2]");
+    }
+
+    #[tokio::test]
+    async fn times_out_when_the_response_stalls() {
+        let base_url = serve_slowly(&["{", "}"], Duration::from_millis(300));
+
+        let error = get(&session(base_url)).await.unwrap_err();
+
+        assert_eq!(
+            error
+                .downcast_ref::<WeatherkitRequestError>()
+                .map(WeatherkitRequestError::exit_code),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn parses_retry_after_date() {
+        let date = (Utc::now() + chrono::Duration::seconds(60))
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        let delay = parse_retry_after(&date).unwrap();
+        assert!(
+            delay > Duration::from_secs(55) && delay <= Duration::from_secs(60),
+            "{:?}",
+            delay
+        );
+
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+        assert_eq!(parse_retry_after("soon"), None);
     }
 }
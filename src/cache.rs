@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::config;
+use anyhow::{anyhow, Context};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::PathBuf;
+
+/// Returns the directory wetter caches data in.
+pub fn dir() -> Option<PathBuf> {
+    config::xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// Reads a cache entry, treating a missing or unreadable entry as absent.
+pub fn read<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let contents = fs::read(dir()?.join(name)).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+/// Lists the names of the entries in a directory of the cache, relative to the cache root.
+pub fn list(dir_name: &str) -> Vec<String> {
+    let Some(entries) = dir().and_then(|x| fs::read_dir(x.join(dir_name)).ok()) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".json"))
+        .map(|name| format!("{}/{}", dir_name, name))
+        .collect()
+}
+
+/// Writes a cache entry so that only the current user can read it. The entry is written to a
+/// temporary file first so that concurrent readers never see a partial entry.
+pub fn write<T: Serialize>(name: &str, value: &T) -> anyhow::Result<()> {
+    let path = dir()
+        .ok_or_else(|| anyhow!("Could not determine the cache directory"))?
+        .join(name);
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid cache entry name {}", name))?;
+
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder
+        .create(parent)
+        .with_context(|| format!("Could not create cache directory {}", parent.display()))?;
+
+    let temporary_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options
+        .open(&temporary_path)
+        .with_context(|| format!("Could not create {}", temporary_path.display()))?;
+    file.write_all(&serde_json::to_vec(value)?)?;
+    fs::rename(&temporary_path, &path)
+        .with_context(|| format!("Could not write {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Writes a cache entry, ignoring any failure. The cache only saves work, so failing to write
+/// an entry costs no more than doing that work again next time.
+pub fn write_best_effort<T: Serialize>(name: &str, value: &T) {
+    let _ = write(name, value);
+}
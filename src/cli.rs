@@ -1,6 +1,13 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use clap::{Parser, ValueEnum};
+use crate::geolocation::ProviderKind;
+use crate::i18n::Language;
+use crate::units::{LengthUnit, PressureUnit, SpeedUnit, TemperatureUnit, UnitSystem};
+use crate::weatherkit::DataSet;
+use chrono_tz::Tz;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, ValueEnum, Debug)]
 pub enum Forecast {
@@ -14,12 +21,258 @@ pub enum Forecast {
     NextHour,
     /// Get any alerts
     Alerts,
+    /// Get the daily forecast and any alerts together, e.g. to write as an iCalendar feed
+    Calendar,
+}
+
+impl Forecast {
+    /// The datasets needed to display the forecast.
+    pub const fn datasets(&self) -> &'static [DataSet] {
+        match self {
+            Self::Current => &[DataSet::CurrentWeather, DataSet::ForecastDaily],
+            Self::Weekly => &[DataSet::ForecastDaily],
+            Self::Hourly => &[DataSet::ForecastHourly],
+            Self::NextHour => &[DataSet::ForecastNextHour],
+            Self::Alerts => &[DataSet::WeatherAlerts],
+            Self::Calendar => &[DataSet::ForecastDaily, DataSet::WeatherAlerts],
+        }
+    }
+}
+
+/// How to write the weather out.
+#[derive(Copy, Clone, Eq, PartialEq, Default, ValueEnum, Debug)]
+pub enum Output {
+    /// Boxes and tables for reading in a terminal
+    #[default]
+    Text,
+    /// A JSON document with quantities in the chosen units and times in the display timezone
+    Json,
+    /// The hours, days or minutes of the forecast, or the current weather, as CSV with a row for
+    /// each of them
+    Csv,
+    /// The hours, days or minutes of the forecast, or the current weather, as newline-delimited
+    /// JSON objects
+    Ndjson,
+    /// The hours, days or minutes of the forecast, or the current weather, as points in the
+    /// InfluxDB line protocol, e.g. for Telegraf's exec input
+    Influx,
+    /// The hours, days or minutes of the forecast, or the current weather, in Graphite's
+    /// plaintext protocol
+    Graphite,
+    /// The days of the weekly forecast as all-day events and the alerts as events lasting until
+    /// they expire, or both for the calendar, as an iCalendar feed that calendar applications
+    /// can subscribe to
+    Ics,
 }
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The forecast to display
     #[arg(short, long, value_enum, default_value_t = Forecast::Current)]
     pub forecast: Forecast,
+
+    /// The config file to use instead of ~/.config/wetter/config.toml
+    #[arg(long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// How long to wait for a response, or for more of it once it has started, in seconds,
+    /// overriding the config file
+    #[arg(long, value_name = "SECS", global = true)]
+    pub read_timeout: Option<u64>,
+
+    /// How many times to retry requests that failed transiently, overriding the config file
+    #[arg(long, value_name = "N", global = true)]
+    pub retries: Option<u32>,
+
+    /// The language of the weather descriptions and of wetter's own text, e.g. de or en-GB.
+    /// Defaults to the language of the locale set by LC_ALL, LC_MESSAGES or LANG
+    #[arg(long, value_name = "TAG", global = true)]
+    pub lang: Option<Language>,
+
+    /// The timezone to display times in, e.g. UTC, instead of the location's own timezone
+    #[arg(long, value_name = "TZ", global = true)]
+    pub display_timezone: Option<Tz>,
+
+    /// Download fresh weather data even if the cached data hasn't expired yet
+    #[arg(long, conflicts_with = "offline", global = true)]
+    pub refresh: bool,
+
+    /// Only use cached weather data, even if it has expired, without touching the network
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// With --forecast alerts, also fetch and show the full details of each alert
+    #[arg(long)]
+    pub details: bool,
+
+    /// With --forecast hourly, how many hours to show instead of all of them
+    #[arg(long, value_name = "N")]
+    pub hours: Option<usize>,
+
+    /// The format to write the weather in
+    #[arg(short, long, value_enum, default_value_t, global = true)]
+    pub output: Output,
+
+    /// With --output csv, ndjson, influx or graphite, the columns to write, in order, instead of all of them
+    #[arg(long, value_name = "COLUMN", value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    #[command(flatten)]
+    pub location: LocationArgs,
+
+    #[command(flatten)]
+    pub time: TimeArgs,
+
+    #[command(flatten)]
+    pub units: UnitArgs,
+
+    #[command(flatten)]
+    pub credentials: CredentialArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check the WeatherKit credentials and inspect the token signed with them
+    Auth {
+        /// Print the signed token, e.g. for use with curl
+        #[arg(long)]
+        print_token: bool,
+    },
+    /// Show the full details of a weather alert, including what to do about it
+    Alert {
+        /// The ID of the alert, as listed by --forecast alerts
+        id: String,
+    },
+    /// Serve the current conditions and the hourly forecast as Prometheus metrics, refreshing
+    /// them whenever WeatherKit says they expire
+    ServeMetrics {
+        /// The address to serve /metrics on
+        #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:9797")]
+        listen: SocketAddr,
+        /// How many hours of the hourly forecast to export
+        #[arg(long, value_name = "N", default_value_t = 12)]
+        forecast_hours: usize,
+    },
+}
+
+// Options for choosing the location to forecast instead of geolocating by IP address. This
+// isn't a doc comment as clap would otherwise use it as the about text of the whole command.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Location")]
+pub struct LocationArgs {
+    /// The latitude of the location, in degrees
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        requires_all = ["lon", "timezone", "country_code"]
+    )]
+    pub lat: Option<f64>,
+
+    /// The longitude of the location, in degrees
+    #[arg(long, allow_negative_numbers = true, requires = "lat")]
+    pub lon: Option<f64>,
+
+    /// The IANA timezone of the location, e.g. Europe/Berlin
+    #[arg(long, requires = "lat")]
+    pub timezone: Option<String>,
+
+    /// The ISO 3166-1 alpha-2 code of the country the location is in, e.g. DE
+    #[arg(long, requires = "lat")]
+    pub country_code: Option<String>,
+
+    /// The name of the city to display for the location
+    #[arg(long, requires = "lat")]
+    pub city: Option<String>,
+
+    /// The name of the country to display for the location
+    #[arg(long, requires = "lat")]
+    pub country: Option<String>,
+
+    /// A place name or postcode to look up offline, e.g. "Berlin", "Berlin, DE" or "10115, DE"
+    #[arg(short, long, conflicts_with = "lat")]
+    pub place: Option<String>,
+
+    /// The name of a location saved in the config file
+    #[arg(short = 'l', long = "location", value_name = "NAME", conflicts_with_all = ["lat", "place"])]
+    pub saved_location: Option<String>,
+
+    /// The location providers to try in order, overriding the config file
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "PROVIDERS")]
+    pub geolocation: Vec<ProviderKind>,
+
+    /// A GeoNames place or postal code dump to search in addition to the bundled gazetteer
+    #[arg(long, value_name = "PATH", requires = "place")]
+    pub gazetteer: Vec<PathBuf>,
+}
+
+// The period to get the weather for. Times without a UTC offset are in the location's timezone.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Time")]
+pub struct TimeArgs {
+    /// The start of the daily and hourly forecasts, e.g. 2026-10-01 or 2026-10-01T06:00
+    #[arg(long, value_name = "TIME")]
+    pub from: Option<String>,
+
+    /// The end of the daily and hourly forecasts, inclusive of the day if only a date is given
+    #[arg(long, value_name = "TIME")]
+    pub to: Option<String>,
+
+    /// The time to get the current weather as of, e.g. 2026-10-16T08:00
+    #[arg(long, value_name = "TIME")]
+    pub at: Option<String>,
+}
+
+// The units to display quantities in, which take precedence over the config file.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Units")]
+pub struct UnitArgs {
+    /// The preset of units to use, which the options below override
+    #[arg(long, value_name = "SYSTEM", global = true)]
+    pub units: Option<UnitSystem>,
+
+    /// The unit of temperatures
+    #[arg(long, value_name = "UNIT", global = true, ignore_case = true)]
+    pub temperature: Option<TemperatureUnit>,
+
+    /// The unit of wind speeds
+    #[arg(long, value_name = "UNIT", global = true, ignore_case = true)]
+    pub wind: Option<SpeedUnit>,
+
+    /// The unit of air pressure
+    #[arg(long, value_name = "UNIT", global = true, ignore_case = true)]
+    pub pressure: Option<PressureUnit>,
+
+    /// The unit of precipitation amounts and rates
+    #[arg(long, value_name = "UNIT", global = true, ignore_case = true)]
+    pub precipitation: Option<LengthUnit>,
+}
+
+// The WeatherKit credentials, which take precedence over those in the config file.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Credentials")]
+pub struct CredentialArgs {
+    /// The ID of the WeatherKit key
+    #[arg(long, global = true, env = "WETTER_KEY_ID")]
+    pub key_id: Option<String>,
+
+    /// The ID of the WeatherKit service
+    #[arg(long, global = true, env = "WETTER_SERVICE_ID")]
+    pub service_id: Option<String>,
+
+    /// The ID of the Apple developer team
+    #[arg(long, global = true, env = "WETTER_TEAM_ID")]
+    pub team_id: Option<String>,
+
+    /// The path to the .p8 private key of the WeatherKit key
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "WETTER_PRIVATE_KEY_PATH",
+        global = true
+    )]
+    pub private_key: Option<PathBuf>,
 }
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::auth::ONE_HOUR;
+use crate::geolocation::{
+    check_coordinates, Location, ProviderKind, GPSD_DEFAULT_ADDRESS, IP_API_URL_BASE_PATH,
+};
+use crate::retry::RetryPolicy;
+use crate::units::{LengthUnit, PressureUnit, SpeedUnit, TemperatureUnit, UnitSystem};
+use crate::weatherkit::WEATHERKIT_API_BASE_URL;
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The name of wetter's directories in the XDG base directories.
+const DIR_NAME: &str = "wetter";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Returns wetter's directory in an XDG base directory, following the XDG base directory
+/// specification on every platform. The base directory is read from the given environment
+/// variable, falling back to the given directory relative to the home directory.
+pub fn xdg_dir(variable: &str, home_fallback: &str) -> Option<PathBuf> {
+    env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|x| x.is_absolute())
+        .or_else(|| dirs::home_dir().map(|x| x.join(home_fallback)))
+        .map(|x| x.join(DIR_NAME))
+}
+
+/// The user's configuration, read from `$XDG_CONFIG_HOME/wetter/config.toml`.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// The name of the saved location to use when none is given on the command line.
+    pub default_location: Option<String>,
+    /// Locations that can be selected by name with `--location`.
+    pub locations: BTreeMap<String, SavedLocation>,
+    /// GeoNames dumps to search in addition to the bundled gazetteer.
+    pub gazetteer: Vec<PathBuf>,
+    /// How to find the location when none is given on the command line.
+    pub geolocation: GeolocationConfig,
+    /// The WeatherKit credentials to sign requests with.
+    pub credentials: CredentialsConfig,
+    /// Timeouts and retries for network requests.
+    pub network: NetworkConfig,
+    /// The units to display quantities in.
+    pub units: UnitsConfig,
+}
+
+/// The `[units]` section of the configuration file.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct UnitsConfig {
+    /// The preset of units to use, which the other settings override.
+    pub system: UnitSystem,
+    /// The unit of temperatures.
+    pub temperature: Option<TemperatureUnit>,
+    /// The unit of wind speeds.
+    pub wind: Option<SpeedUnit>,
+    /// The unit of air pressure.
+    pub pressure: Option<PressureUnit>,
+    /// The unit of precipitation amounts and rates.
+    pub precipitation: Option<LengthUnit>,
+}
+
+/// The `[network]` section of the configuration file.
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// The base URL of the WeatherKit REST API, e.g. to point wetter at a mock server.
+    pub api_base_url: String,
+    /// How long to wait for a connection to be established, in seconds.
+    pub connect_timeout: u64,
+    /// How long to wait for WeatherKit to send the response or, once it has started, the next
+    /// part of it, in seconds. Unlike a limit on the whole request, this doesn't fail slow
+    /// downloads of large responses as long as they make progress.
+    pub read_timeout: u64,
+    /// How many times to retry requests that failed transiently.
+    pub retries: u32,
+    /// The upper bound of the delay before the first retry, in seconds.
+    pub initial_backoff: f64,
+    /// The upper bound of any delay between retries, in seconds.
+    pub max_backoff: f64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            api_base_url: WEATHERKIT_API_BASE_URL.to_owned(),
+            connect_timeout: 10,
+            read_timeout: 30,
+            retries: 3,
+            initial_backoff: 0.5,
+            max_backoff: 30.0,
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// The read timeout, with the given number of seconds taking precedence over the
+    /// configured one.
+    pub fn read_timeout(&self, read_timeout: Option<u64>) -> Duration {
+        Duration::from_secs(read_timeout.unwrap_or(self.read_timeout))
+    }
+
+    /// Builds the policy for retrying failed requests, with the given number of retries taking
+    /// precedence over the configured one.
+    pub fn retry_policy(&self, retries: Option<u32>) -> anyhow::Result<RetryPolicy> {
+        Ok(RetryPolicy {
+            retries: retries.unwrap_or(self.retries),
+            initial_backoff: backoff("initial_backoff", self.initial_backoff)?,
+            max_backoff: backoff("max_backoff", self.max_backoff)?,
+        })
+    }
+}
+
+fn backoff(name: &str, seconds: f64) -> anyhow::Result<Duration> {
+    Duration::try_from_secs_f64(seconds).map_err(|_| {
+        anyhow!(
+            "network.{} in the config file must be a non-negative number of seconds, not {}.",
+            name,
+            seconds
+        )
+    })
+}
+
+/// The `[credentials]` section of the configuration file.
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct CredentialsConfig {
+    /// The ID of the WeatherKit key.
+    pub key_id: Option<String>,
+    /// The ID of the WeatherKit service.
+    pub service_id: Option<String>,
+    /// The ID of the Apple developer team.
+    pub team_id: Option<String>,
+    /// The path to the `.p8` private key downloaded from the Apple developer portal.
+    pub private_key_path: Option<PathBuf>,
+    /// How long signed tokens are valid for, in seconds. Tokens are cached and reused until
+    /// shortly before they expire.
+    pub token_lifetime: u64,
+}
+
+impl Default for CredentialsConfig {
+    fn default() -> Self {
+        Self {
+            key_id: None,
+            service_id: None,
+            team_id: None,
+            private_key_path: None,
+            token_lifetime: ONE_HOUR,
+        }
+    }
+}
+
+/// The `[geolocation]` section of the configuration file.
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeolocationConfig {
+    /// The providers to try, in order, until one of them succeeds.
+    pub providers: Vec<ProviderKind>,
+    /// The address of the gpsd daemon.
+    pub gpsd_address: String,
+    /// How long to wait for a fix from gpsd, in seconds.
+    pub gpsd_timeout: u64,
+    /// The ip-api endpoint to query, e.g. to use the HTTPS endpoint of a paid plan.
+    pub ip_api_url: String,
+}
+
+impl Default for GeolocationConfig {
+    fn default() -> Self {
+        Self {
+            providers: ProviderKind::DEFAULT_ORDER.to_vec(),
+            gpsd_address: GPSD_DEFAULT_ADDRESS.to_owned(),
+            gpsd_timeout: 5,
+            ip_api_url: IP_API_URL_BASE_PATH.to_owned(),
+        }
+    }
+}
+
+/// A named location in the configuration file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SavedLocation {
+    pub lat: f64,
+    pub lon: f64,
+    pub timezone: String,
+    pub country_code: String,
+    #[serde(default)]
+    pub city: String,
+    #[serde(default)]
+    pub country: String,
+}
+
+impl Config {
+    /// Returns the directory wetter's configuration lives in.
+    pub fn dir() -> Option<PathBuf> {
+        xdg_dir("XDG_CONFIG_HOME", ".config")
+    }
+
+    /// Loads the configuration from the given path, or from the default location if no path
+    /// is given. A missing default configuration file is not an error.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => match Self::dir().map(|x| x.join(CONFIG_FILE_NAME)) {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Could not read config file {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Could not parse config file {}", path.display()))
+    }
+
+    /// Looks up the saved location with the given name.
+    pub fn location(&self, name: &str) -> anyhow::Result<Location> {
+        match self.locations.get(name) {
+            Some(saved) => saved
+                .try_into()
+                .with_context(|| format!("The saved location \"{}\" is invalid", name)),
+            None if self.locations.is_empty() => Err(anyhow!(
+                "There is no saved location named \"{}\" as the config file has no locations.",
+                name
+            )),
+            None => Err(anyhow!(
+                "There is no saved location named \"{}\". Saved locations are: {}",
+                name,
+                self.locations
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Looks up the default saved location, if one is configured.
+    pub fn default_location(&self) -> anyhow::Result<Option<Location>> {
+        self.default_location
+            .as_deref()
+            .map(|name| self.location(name))
+            .transpose()
+    }
+}
+
+impl TryFrom<&SavedLocation> for Location {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &SavedLocation) -> anyhow::Result<Self> {
+        check_coordinates(value.lat, value.lon)?;
+
+        Ok(Self {
+            country: value.country.clone(),
+            country_code: value.country_code.to_uppercase(),
+            city: value.city.clone(),
+            lat: value.lat,
+            lon: value.lon,
+            timezone: value.timezone.clone(),
+        })
+    }
+}
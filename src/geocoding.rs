@@ -0,0 +1,523 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::Location;
+use anyhow::{anyhow, Context};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// The gazetteer bundled with wetter, in the GeoNames `cities*.txt` format.
+const BUNDLED_GAZETTEER: &str = include_str!("../assets/cities.txt");
+
+/// The number of columns in a GeoNames place dump (e.g. `cities500.txt`).
+const PLACE_COLUMNS: usize = 19;
+/// The number of columns in a GeoNames postal code dump (e.g. `DE.txt` from `/export/zip`).
+const POSTAL_CODE_COLUMNS: usize = 12;
+
+/// How many times more populous than the runner-up a place has to be for an ambiguous name
+/// to resolve to it without further qualification.
+const POPULATION_DOMINANCE: u64 = 10;
+/// The maximum number of candidates listed when a name is ambiguous.
+const MAX_CANDIDATES: usize = 5;
+
+#[derive(Error, Debug)]
+pub enum GeocodingError {
+    #[error("No place matching \"{0}\" was found in the gazetteer.")]
+    NotFound(String),
+    #[error("Several places match \"{query}\": {candidates}. Add a country or region code, e.g. \"{example}\".")]
+    Ambiguous {
+        query: String,
+        candidates: String,
+        example: String,
+    },
+    #[error("Could not determine the timezone of \"{0}\" as the gazetteer has no places with a timezone.")]
+    UnknownTimezone(String),
+}
+
+#[derive(Debug)]
+struct Place {
+    name: String,
+    /// The normalised names the place can be looked up by.
+    keys: Vec<String>,
+    postal_code: Option<String>,
+    lat: f64,
+    lon: f64,
+    country_code: String,
+    admin1_code: String,
+    population: u64,
+    timezone: Option<String>,
+}
+
+/// An offline index of places that can be resolved to a [`Location`].
+pub struct Gazetteer {
+    places: Vec<Place>,
+}
+
+/// How well a place matched the query, lower is better.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+enum MatchQuality {
+    Exact,
+    Prefix,
+    Fuzzy(usize),
+}
+
+impl Place {
+    fn parse(line: &str) -> Option<anyhow::Result<Self>> {
+        if line.trim().is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let columns = line.split('\t').collect::<Vec<_>>();
+        Some(match columns.len() {
+            PLACE_COLUMNS => Self::parse_place(&columns),
+            POSTAL_CODE_COLUMNS => Self::parse_postal_code(&columns),
+            n => Err(anyhow!(
+                "Expected {} or {} tab-separated columns but found {}",
+                PLACE_COLUMNS,
+                POSTAL_CODE_COLUMNS,
+                n
+            )),
+        })
+    }
+
+    fn parse_place(columns: &[&str]) -> anyhow::Result<Self> {
+        let mut keys = [columns[1], columns[2]]
+            .into_iter()
+            .chain(columns[3].split(',').filter(|x| !x.is_empty()))
+            .flat_map(keys)
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        Ok(Self {
+            name: columns[1].to_owned(),
+            keys,
+            postal_code: None,
+            lat: columns[4].parse().context("Invalid latitude")?,
+            lon: columns[5].parse().context("Invalid longitude")?,
+            country_code: columns[8].to_owned(),
+            admin1_code: columns[10].to_owned(),
+            population: columns[14].parse().unwrap_or(0),
+            timezone: Some(columns[17].to_owned()).filter(|x| !x.is_empty()),
+        })
+    }
+
+    fn parse_postal_code(columns: &[&str]) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: columns[2].to_owned(),
+            keys: keys(columns[2]).to_vec(),
+            postal_code: Some(normalise(columns[1])),
+            lat: columns[9].parse().context("Invalid latitude")?,
+            lon: columns[10].parse().context("Invalid longitude")?,
+            country_code: columns[0].to_owned(),
+            admin1_code: columns[4].to_owned(),
+            population: 0,
+            timezone: None,
+        })
+    }
+
+    fn matches_qualifiers(&self, qualifiers: &[String]) -> bool {
+        qualifiers.iter().all(|qualifier| {
+            qualifier.eq_ignore_ascii_case(&self.country_code)
+                || qualifier.eq_ignore_ascii_case(&self.admin1_code)
+                || qualifier
+                    .eq_ignore_ascii_case(&format!("{}-{}", self.country_code, self.admin1_code))
+        })
+    }
+
+    fn match_name(&self, name: &str) -> Option<MatchQuality> {
+        if self.keys.iter().any(|key| key == name) {
+            return Some(MatchQuality::Exact);
+        }
+
+        if self.keys.iter().any(|key| key.starts_with(name)) {
+            return Some(MatchQuality::Prefix);
+        }
+
+        let max_distance = (name.chars().count() / 4).max(1);
+        self.keys
+            .iter()
+            .filter(|key| key.chars().count().abs_diff(name.chars().count()) <= max_distance)
+            .map(|key| levenshtein(key, name))
+            .filter(|distance| *distance <= max_distance)
+            .min()
+            .map(MatchQuality::Fuzzy)
+    }
+
+    fn match_postal_code(&self, postal_code: &str) -> Option<MatchQuality> {
+        match &self.postal_code {
+            Some(x) if x == postal_code => Some(MatchQuality::Exact),
+            Some(x) if x.starts_with(postal_code) => Some(MatchQuality::Prefix),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> String {
+        let name = match &self.postal_code {
+            Some(postal_code) => format!("{} {}", postal_code.to_uppercase(), self.name),
+            None => self.name.clone(),
+        };
+
+        if self.admin1_code.is_empty() {
+            format!("{}, {}", name, self.country_code)
+        } else {
+            format!("{}, {}-{}", name, self.country_code, self.admin1_code)
+        }
+    }
+
+    /// The squared equirectangular distance to a point, which is good enough for finding the
+    /// closest place.
+    fn distance_to(&self, lat: f64, lon: f64) -> f64 {
+        let x = (lon - self.lon).to_radians() * ((lat + self.lat) / 2.0).to_radians().cos();
+        let y = (lat - self.lat).to_radians();
+        x * x + y * y
+    }
+
+    fn to_location(&self, timezone: String) -> Location {
+        Location {
+            country: String::new(),
+            country_code: self.country_code.clone(),
+            city: self.name.clone(),
+            lat: self.lat,
+            lon: self.lon,
+            timezone,
+        }
+    }
+}
+
+impl Gazetteer {
+    /// Loads the bundled gazetteer along with any user-supplied GeoNames place or postal code
+    /// dumps.
+    pub fn load<P: AsRef<Path>>(paths: &[P]) -> anyhow::Result<Self> {
+        let mut gazetteer = Self { places: Vec::new() };
+        gazetteer
+            .extend(BUNDLED_GAZETTEER)
+            .context("Could not parse the bundled gazetteer")?;
+
+        for path in paths {
+            let path = path.as_ref();
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Could not read gazetteer {}", path.display()))?;
+            gazetteer
+                .extend(&contents)
+                .with_context(|| format!("Could not parse gazetteer {}", path.display()))?;
+        }
+
+        Ok(gazetteer)
+    }
+
+    fn extend(&mut self, contents: &str) -> anyhow::Result<()> {
+        for (i, line) in contents.lines().enumerate() {
+            if let Some(place) = Place::parse(line) {
+                self.places
+                    .push(place.with_context(|| format!("Malformed line {}", i + 1))?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves a query such as "Berlin", "Berlin, DE", "Springfield, IL, US" or "10115, DE"
+    /// to a location.
+    pub fn lookup(&self, query: &str) -> anyhow::Result<Location> {
+        let mut parts = query.split(',').map(str::trim).filter(|x| !x.is_empty());
+        let name = normalise(parts.next().unwrap_or_default());
+        let qualifiers = parts.map(str::to_owned).collect::<Vec<_>>();
+        let is_postal_code = name.chars().any(|c| c.is_ascii_digit());
+
+        let mut candidates = self
+            .places
+            .iter()
+            .filter(|place| place.matches_qualifiers(&qualifiers))
+            .filter_map(|place| {
+                let quality = if is_postal_code {
+                    place.match_postal_code(&name)
+                } else {
+                    place.match_name(&name)
+                };
+                quality.map(|quality| (quality, place))
+            })
+            .collect::<Vec<_>>();
+
+        let best = candidates
+            .iter()
+            .map(|(quality, _)| *quality)
+            .min()
+            .ok_or_else(|| GeocodingError::NotFound(query.to_owned()))?;
+        candidates.retain(|(quality, _)| *quality == best);
+        candidates.sort_by_key(|(_, place)| Reverse(place.population));
+
+        // Postal code dumps list one entry per place sharing the code, so those are the same
+        // location for our purposes
+        candidates.dedup_by(|(_, a), (_, b)| {
+            a.postal_code.is_some()
+                && a.postal_code == b.postal_code
+                && a.country_code == b.country_code
+        });
+
+        let place = match candidates.as_slice() {
+            [(_, place)] => *place,
+            [(_, first), (_, second), ..]
+                if first.population >= second.population.max(1) * POPULATION_DOMINANCE =>
+            {
+                *first
+            }
+            _ => {
+                let labels = candidates
+                    .iter()
+                    .take(MAX_CANDIDATES)
+                    .map(|(_, place)| place.label())
+                    .collect::<Vec<_>>();
+                let ellipsis = if candidates.len() > MAX_CANDIDATES {
+                    "; ..."
+                } else {
+                    ""
+                };
+
+                return Err(GeocodingError::Ambiguous {
+                    query: query.to_owned(),
+                    candidates: format!("{}{}", labels.join("; "), ellipsis),
+                    example: labels[0].clone(),
+                }
+                .into());
+            }
+        };
+
+        let timezone = match &place.timezone {
+            Some(timezone) => timezone.clone(),
+            None => self
+                .nearest(place.lat, place.lon)
+                .map(|x| x.timezone)
+                .ok_or_else(|| GeocodingError::UnknownTimezone(query.to_owned()))?,
+        };
+
+        Ok(place.to_location(timezone))
+    }
+
+    /// Finds the place with a known timezone closest to the given coordinates.
+    pub fn nearest(&self, lat: f64, lon: f64) -> Option<Location> {
+        self.places
+            .iter()
+            .filter_map(|place| place.timezone.as_ref().map(|timezone| (place, timezone)))
+            .min_by(|(a, _), (b, _)| a.distance_to(lat, lon).total_cmp(&b.distance_to(lat, lon)))
+            .map(|(place, timezone)| place.to_location(timezone.clone()))
+    }
+}
+
+/// Lowercases a name and folds common Latin diacritics, transliterating umlauts the German
+/// way so that e.g. "Zürich" and "Zuerich" are the same.
+fn normalise(name: &str) -> String {
+    fold(name, true)
+}
+
+/// The normalised names a place can be looked up by. Besides the normalised name, umlauts are
+/// also folded to the bare vowel, so that e.g. "Zürich", "Zuerich" and "zurich" all match
+/// exactly.
+fn keys(name: &str) -> [String; 2] {
+    [fold(name, true), fold(name, false)]
+}
+
+fn fold(name: &str, transliterate_umlauts: bool) -> String {
+    let mut result = String::with_capacity(name.len());
+
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'å' | 'ā' | 'ą' => result.push('a'),
+            'ä' if !transliterate_umlauts => result.push('a'),
+            'ä' | 'æ' => result.push_str("ae"),
+            'ç' | 'ć' | 'č' => result.push('c'),
+            'ď' | 'đ' => result.push('d'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => result.push('e'),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => result.push('i'),
+            'ł' => result.push('l'),
+            'ñ' | 'ń' | 'ň' => result.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ō' | 'ő' => result.push('o'),
+            'ö' if !transliterate_umlauts => result.push('o'),
+            'ö' | 'ø' | 'œ' => result.push_str("oe"),
+            'ř' => result.push('r'),
+            'ś' | 'š' | 'ş' => result.push('s'),
+            'ß' => result.push_str("ss"),
+            'ť' | 'ţ' => result.push('t'),
+            'ù' | 'ú' | 'û' | 'ū' | 'ů' | 'ű' => result.push('u'),
+            'ü' if !transliterate_umlauts => result.push('u'),
+            'ü' => result.push_str("ue"),
+            'ý' | 'ÿ' => result.push('y'),
+            'ź' | 'ż' | 'ž' => result.push('z'),
+            '-' | '\'' | '.' => {}
+            c if c.is_whitespace() => {
+                if !result.ends_with(' ') {
+                    result.push(' ')
+                }
+            }
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a line of a GeoNames place dump.
+    fn place(
+        name: &str,
+        lat: f64,
+        lon: f64,
+        country: &str,
+        admin1: &str,
+        population: u64,
+    ) -> String {
+        let mut columns = vec![String::new(); PLACE_COLUMNS];
+        columns[1] = name.to_owned();
+        columns[2] = normalise(name);
+        columns[4] = lat.to_string();
+        columns[5] = lon.to_string();
+        columns[8] = country.to_owned();
+        columns[10] = admin1.to_owned();
+        columns[14] = population.to_string();
+        columns[17] = "Etc/UTC".to_owned();
+        columns.join("\t")
+    }
+
+    /// Builds a line of a GeoNames postal code dump.
+    fn postal_code(country: &str, code: &str, name: &str, lat: f64, lon: f64) -> String {
+        let mut columns = vec![String::new(); POSTAL_CODE_COLUMNS];
+        columns[0] = country.to_owned();
+        columns[1] = code.to_owned();
+        columns[2] = name.to_owned();
+        columns[9] = lat.to_string();
+        columns[10] = lon.to_string();
+        columns.join("\t")
+    }
+
+    fn gazetteer(lines: &[String]) -> Gazetteer {
+        let mut gazetteer = Gazetteer { places: Vec::new() };
+        gazetteer.extend(&lines.join("\n")).unwrap();
+        gazetteer
+    }
+
+    fn sample() -> Gazetteer {
+        gazetteer(&[
+            place("Berlin", 52.52, 13.41, "DE", "16", 3_426_354),
+            place("Zürich", 47.37, 8.55, "CH", "ZH", 341_730),
+            place("Springfield", 39.80, -89.64, "US", "IL", 116_250),
+            place("Springfield", 42.10, -72.59, "US", "MA", 153_606),
+            place("Frankfurt am Main", 50.12, 8.68, "DE", "05", 650_000),
+            postal_code("DE", "10115", "Berlin Mitte", 52.53, 13.38),
+            postal_code("DE", "10115", "Berlin", 52.53, 13.38),
+        ])
+    }
+
+    #[test]
+    fn looks_up_exact_names() {
+        let location = sample().lookup("Berlin").unwrap();
+        assert_eq!(location.city, "Berlin");
+        assert_eq!(location.country_code, "DE");
+        assert_eq!(location.timezone, "Etc/UTC");
+    }
+
+    #[test]
+    fn matches_transliterated_names_exactly() {
+        let gazetteer = sample();
+        let zurich = gazetteer
+            .places
+            .iter()
+            .find(|x| x.name == "Zürich")
+            .unwrap();
+
+        for query in ["Zürich", "Zuerich", "zurich", "ZÜRICH"] {
+            assert_eq!(
+                zurich.match_name(&normalise(query)),
+                Some(MatchQuality::Exact),
+                "{}",
+                query
+            );
+            assert_eq!(gazetteer.lookup(query).unwrap().city, "Zürich");
+        }
+    }
+
+    #[test]
+    fn matches_prefixes_and_typos() {
+        let gazetteer = sample();
+        assert_eq!(
+            gazetteer.lookup("Frankfurt").unwrap().city,
+            "Frankfurt am Main"
+        );
+        assert_eq!(gazetteer.lookup("Berln").unwrap().city, "Berlin");
+        assert!(matches!(
+            gazetteer
+                .lookup("Hamburg")
+                .unwrap_err()
+                .downcast_ref::<GeocodingError>(),
+            Some(GeocodingError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reports_ambiguous_names_with_suggestions() {
+        let gazetteer = sample();
+        let error = gazetteer.lookup("Springfield").unwrap_err();
+
+        match error.downcast_ref::<GeocodingError>() {
+            Some(GeocodingError::Ambiguous {
+                candidates,
+                example,
+                ..
+            }) => {
+                assert_eq!(candidates, "Springfield, US-MA; Springfield, US-IL");
+                assert_eq!(example, "Springfield, US-MA");
+            }
+            _ => panic!("Expected an ambiguity error but got {:?}", error),
+        }
+
+        let location = gazetteer.lookup("Springfield, US-IL").unwrap();
+        assert_eq!(location.lat, 39.80);
+        let location = gazetteer.lookup("springfield, ma").unwrap();
+        assert_eq!(location.lat, 42.10);
+    }
+
+    #[test]
+    fn prefers_much_more_populous_places() {
+        let gazetteer = gazetteer(&[
+            place("Paris", 48.85, 2.35, "FR", "11", 2_138_551),
+            place("Paris", 33.66, -95.56, "US", "TX", 24_171),
+        ]);
+        assert_eq!(gazetteer.lookup("Paris").unwrap().country_code, "FR");
+    }
+
+    #[test]
+    fn merges_places_sharing_a_postal_code() {
+        let location = sample().lookup("10115, DE").unwrap();
+        assert_eq!(location.lat, 52.53);
+        // Postal code dumps have no timezones, so the nearest place's is used
+        assert_eq!(location.timezone, "Etc/UTC");
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("berlin", "berlin"), 0);
+        assert_eq!(levenshtein("berlin", "berln"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+}
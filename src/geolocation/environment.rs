@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::{Location, LocationProvider};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use std::env;
+
+const LAT_VAR: &str = "WETTER_LAT";
+const LON_VAR: &str = "WETTER_LON";
+const TIMEZONE_VAR: &str = "WETTER_TIMEZONE";
+const COUNTRY_CODE_VAR: &str = "WETTER_COUNTRY_CODE";
+const CITY_VAR: &str = "WETTER_CITY";
+const COUNTRY_VAR: &str = "WETTER_COUNTRY";
+
+/// Reads the location from `WETTER_*` environment variables.
+pub struct EnvironmentProvider;
+
+fn var(name: &str) -> anyhow::Result<String> {
+    env::var(name).map_err(|_| anyhow!("{} is not set.", name))
+}
+
+#[async_trait]
+impl LocationProvider for EnvironmentProvider {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    async fn locate(&self) -> anyhow::Result<Location> {
+        let lat = var(LAT_VAR)?
+            .parse()
+            .with_context(|| format!("{} is not a valid latitude", LAT_VAR))?;
+        let lon = var(LON_VAR)?
+            .parse()
+            .with_context(|| format!("{} is not a valid longitude", LON_VAR))?;
+
+        Ok(Location {
+            country: env::var(COUNTRY_VAR).unwrap_or_default(),
+            country_code: var(COUNTRY_CODE_VAR)?.to_uppercase(),
+            city: env::var(CITY_VAR).unwrap_or_default(),
+            lat,
+            lon,
+            timezone: var(TIMEZONE_VAR)?,
+        })
+    }
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geocoding::Gazetteer;
+use crate::geolocation::{Location, LocationProvider};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time;
+
+pub const GPSD_DEFAULT_ADDRESS: &str = "127.0.0.1:2947";
+
+const WATCH_COMMAND: &[u8] = b"?WATCH={\"enable\":true,\"json\":true};\n";
+
+/// The parts of a gpsd report we care about. Only TPV (time-position-velocity) reports with
+/// a 2D or 3D fix carry a usable position.
+#[derive(Deserialize, Debug)]
+struct Report {
+    class: String,
+    mode: Option<u8>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+}
+
+/// Reads the position from a GPS receiver through gpsd. As gpsd only knows the coordinates,
+/// the country and timezone are taken from the nearest place in the gazetteer.
+pub struct GpsdProvider {
+    address: String,
+    timeout: Duration,
+    gazetteer: Vec<PathBuf>,
+}
+
+impl GpsdProvider {
+    pub fn new(address: String, timeout: Duration, gazetteer: Vec<PathBuf>) -> Self {
+        Self {
+            address,
+            timeout,
+            gazetteer,
+        }
+    }
+
+    async fn read_fix(&self) -> anyhow::Result<(f64, f64)> {
+        let mut stream = TcpStream::connect(&self.address)
+            .await
+            .with_context(|| format!("Could not connect to gpsd at {}", self.address))?;
+        stream.write_all(WATCH_COMMAND).await?;
+
+        let mut lines = BufReader::new(stream).lines();
+        while let Some(line) = lines.next_line().await? {
+            let Ok(report) = serde_json::from_str::<Report>(&line) else {
+                continue;
+            };
+
+            if let ("TPV", Some(2..), Some(lat), Some(lon)) =
+                (report.class.as_str(), report.mode, report.lat, report.lon)
+            {
+                return Ok((lat, lon));
+            }
+        }
+
+        Err(anyhow!(
+            "gpsd closed the connection before reporting a fix."
+        ))
+    }
+}
+
+#[async_trait]
+impl LocationProvider for GpsdProvider {
+    fn name(&self) -> &'static str {
+        "gpsd"
+    }
+
+    async fn locate(&self) -> anyhow::Result<Location> {
+        let (lat, lon) = time::timeout(self.timeout, self.read_fix())
+            .await
+            .map_err(|_| anyhow!("Timed out waiting for a fix from gpsd."))??;
+
+        let nearest = Gazetteer::load(&self.gazetteer)?
+            .nearest(lat, lon)
+            .ok_or_else(|| anyhow!("The gazetteer is empty."))?;
+
+        Ok(Location {
+            lat,
+            lon,
+            ..nearest
+        })
+    }
+}
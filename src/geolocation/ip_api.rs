@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::{Location, LocationProvider};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use reqwest::{Client, Method};
+use serde::Deserialize;
+use std::time::Duration;
+
+pub const IP_API_URL_BASE_PATH: &str = "http://ip-api.com/json/";
+
+/// The response from ip-api. Only `status` and `message` are present when the lookup fails.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IpApiResponse {
+    status: String,
+    message: Option<String>,
+    #[serde(default)]
+    country: String,
+    country_code: Option<String>,
+    #[serde(default)]
+    city: String,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    timezone: Option<String>,
+}
+
+/// Geolocates the public IP address using ip-api.com.
+pub struct IpApiProvider {
+    client: Client,
+    url: String,
+    timeout: Duration,
+}
+
+impl IpApiProvider {
+    /// The response is tiny, so the timeout covers the whole request.
+    pub fn new(client: Client, url: String, timeout: Duration) -> Self {
+        Self {
+            client,
+            url,
+            timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl LocationProvider for IpApiProvider {
+    fn name(&self) -> &'static str {
+        "ip-api"
+    }
+
+    // 49619 gets us fields: status,message,country,countryCode,city,lat,lon,timezone
+    async fn locate(&self) -> anyhow::Result<Location> {
+        let request = self
+            .client
+            .request(Method::GET, &self.url)
+            .query(&[("fields", 49619)])
+            .timeout(self.timeout)
+            .build()
+            .context("Failed to build request")?;
+
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .context("Failed to execute request")?
+            .error_for_status()?
+            .json::<IpApiResponse>()
+            .await
+            .context("Failed to decode response")?;
+
+        if response.status != "success" {
+            return Err(anyhow!(
+                "The lookup failed with status \"{}\": {}",
+                response.status,
+                response.message.as_deref().unwrap_or("no reason given")
+            ));
+        }
+
+        match (
+            response.country_code,
+            response.lat,
+            response.lon,
+            response.timezone,
+        ) {
+            (Some(country_code), Some(lat), Some(lon), Some(timezone)) => Ok(Location {
+                country: response.country,
+                country_code,
+                city: response.city,
+                lat,
+                lon,
+                timezone,
+            }),
+            _ => Err(anyhow!(
+                "The lookup succeeded but the response is missing required fields."
+            )),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::{Location, LocationProvider};
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+/// Provides a fixed location, such as the default location from the config file.
+pub struct ManualProvider {
+    location: Option<Location>,
+}
+
+impl ManualProvider {
+    pub fn new(location: Option<Location>) -> Self {
+        Self { location }
+    }
+}
+
+#[async_trait]
+impl LocationProvider for ManualProvider {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    async fn locate(&self) -> anyhow::Result<Location> {
+        self.location
+            .clone()
+            .ok_or_else(|| anyhow!("No default location is configured."))
+    }
+}
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::cli::LocationArgs;
+use crate::i18n::Language;
+use anyhow::{anyhow, bail};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+mod environment;
+mod gpsd;
+mod ip_api;
+mod manual;
+mod provider;
+
+pub use environment::EnvironmentProvider;
+pub use gpsd::{GpsdProvider, GPSD_DEFAULT_ADDRESS};
+pub use ip_api::{IpApiProvider, IP_API_URL_BASE_PATH};
+pub use manual::ManualProvider;
+pub use provider::{LocationProvider, ProviderChain, ProviderKind};
+
+/// Fails unless the coordinates are on the globe, as WeatherKit would only reject them with an
+/// unhelpful bad request.
+pub fn check_coordinates(lat: f64, lon: f64) -> anyhow::Result<()> {
+    if !(-90.0..=90.0).contains(&lat) {
+        bail!(
+            "The latitude {} is out of range, it must be between -90 and 90.",
+            lat
+        );
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        bail!(
+            "The longitude {} is out of range, it must be between -180 and 180.",
+            lon
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub country: String,
+    pub country_code: String,
+    pub city: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub timezone: String,
+}
+
+impl Location {
+    /// Builds a location from the coordinates given on the command line, if any, without
+    /// touching the network.
+    pub fn from_args(args: &LocationArgs) -> anyhow::Result<Option<Self>> {
+        let (Some(lat), Some(lon), Some(country_code), Some(timezone)) =
+            (args.lat, args.lon, &args.country_code, &args.timezone)
+        else {
+            return Ok(None);
+        };
+        check_coordinates(lat, lon)?;
+
+        Ok(Some(Self {
+            country: args.country.clone().unwrap_or_default(),
+            country_code: country_code.to_uppercase(),
+            city: args.city.clone().unwrap_or_default(),
+            lat,
+            lon,
+            timezone: timezone.clone(),
+        }))
+    }
+
+    /// Looks up the location's timezone in the tz database.
+    pub fn tz(&self) -> anyhow::Result<Tz> {
+        self.timezone
+            .parse()
+            .map_err(|_| anyhow!("\"{}\" is not a known IANA timezone", self.timezone))
+    }
+
+    pub fn get_availability_url(&self, base_url: &str) -> String {
+        format!("{}/availability/{}/{}", base_url, self.lat, self.lon)
+    }
+
+    pub fn get_weather_url(&self, base_url: &str, language: &Language) -> String {
+        format!(
+            "{}/weather/{}/{}/{}",
+            base_url,
+            language.tag(),
+            self.lat,
+            self.lon
+        )
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.country.is_empty() {
+            write!(f, "{}, {}", self.country, self.country_code)
+        } else if !self.city.is_empty() {
+            write!(f, "{}, {}", self.city, self.country_code)
+        } else {
+            write!(f, "{}", self.country_code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_coordinates_are_on_the_globe() {
+        assert!(check_coordinates(90.0, -180.0).is_ok());
+        assert!(check_coordinates(-90.0, 180.0).is_ok());
+        assert!(check_coordinates(90.5, 0.0).is_err());
+        assert!(check_coordinates(0.0, -180.5).is_err());
+        assert!(check_coordinates(f64::NAN, 0.0).is_err());
+    }
+}
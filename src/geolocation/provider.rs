@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::Location;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// A source of the location to forecast.
+#[async_trait]
+pub trait LocationProvider {
+    /// A short human-readable name for the provider, used in error messages.
+    fn name(&self) -> &'static str;
+
+    /// Determines the current location, failing if the provider is unavailable or unconfigured.
+    async fn locate(&self) -> anyhow::Result<Location>;
+}
+
+/// The location providers that can be selected in the config file or on the command line.
+#[derive(Copy, Clone, Eq, PartialEq, ValueEnum, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    /// The default location from the config file
+    Static,
+    /// The WETTER_LAT, WETTER_LON, WETTER_TIMEZONE and WETTER_COUNTRY_CODE environment variables
+    Env,
+    /// A GPS receiver via a local gpsd daemon
+    Gpsd,
+    /// Geolocation of the public IP address via ip-api.com
+    IpApi,
+}
+
+impl ProviderKind {
+    /// The order providers are tried in when none is configured.
+    pub const DEFAULT_ORDER: [ProviderKind; 3] = [Self::Static, Self::Env, Self::IpApi];
+}
+
+/// A list of providers that are tried in order until one succeeds.
+pub struct ProviderChain {
+    providers: Vec<Box<dyn LocationProvider + Send + Sync>>,
+}
+
+impl ProviderChain {
+    pub fn new(providers: Vec<Box<dyn LocationProvider + Send + Sync>>) -> Self {
+        Self { providers }
+    }
+
+    pub async fn locate(&self) -> anyhow::Result<Location> {
+        let mut failures = Vec::with_capacity(self.providers.len());
+
+        for provider in &self.providers {
+            match provider.locate().await {
+                Ok(location) => return Ok(location),
+                Err(e) => failures.push(format!("{}: {:#}", provider.name(), e)),
+            }
+        }
+
+        if failures.is_empty() {
+            Err(anyhow!("No location providers are configured."))
+        } else {
+            Err(anyhow!(
+                "Could not determine the location from any provider.\n  {}",
+                failures.join("\n  ")
+            ))
+        }
+    }
+}
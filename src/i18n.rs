@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::weatherkit::{Certainty, PrecipitationType, ResponseType, Severity, Urgency};
+use anyhow::anyhow;
+use chrono::{Datelike, NaiveDate, Weekday};
+use inflector::Inflector;
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const DEFAULT_LANGUAGE_TAG: &str = "en";
+
+/// The locale environment variables, in order of precedence.
+const LOCALE_VARS: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// A BCP 47 language tag, e.g. `de` or `en-GB`, selecting the language of WeatherKit's
+/// localisable fields and of wetter's own text.
+#[derive(Clone, Debug)]
+pub struct Language {
+    tag: String,
+}
+
+impl Language {
+    /// Determines the language from the POSIX locale environment variables, falling back to
+    /// English if they aren't set or name the C locale.
+    pub fn from_env() -> Self {
+        LOCALE_VARS
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| {
+                // e.g. de_DE.UTF-8@euro
+                let tag = value.split(['.', '@']).next()?.replace('_', "-");
+                match tag.as_str() {
+                    "C" | "POSIX" => None,
+                    _ => tag.parse().ok(),
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// The language tag to send to WeatherKit.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The catalog to take wetter's own text from, which is English for languages it hasn't
+    /// been translated into.
+    pub fn catalog(&self) -> Catalog {
+        let primary = self.tag.split('-').next().unwrap_or_default();
+
+        match primary.to_lowercase().as_str() {
+            "de" => Catalog::German,
+            _ => Catalog::English,
+        }
+    }
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self {
+            tag: DEFAULT_LANGUAGE_TAG.to_owned(),
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let valid = !value.is_empty()
+            && value.split('-').all(|subtag| {
+                (1..=8).contains(&subtag.len()) && subtag.chars().all(|x| x.is_ascii_alphanumeric())
+            });
+
+        if valid {
+            Ok(Self {
+                tag: value.to_owned(),
+            })
+        } else {
+            Err(anyhow!(
+                "\"{}\" is not a valid language tag, expected e.g. de or en-GB",
+                value
+            ))
+        }
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.tag)
+    }
+}
+
+/// The text wetter displays itself, as opposed to the text in WeatherKit's responses.
+#[derive(Copy, Clone, Debug)]
+pub enum Message {
+    FeelsLike,
+    Humidity,
+    DewPoint,
+    NoAlerts,
+    Source,
+    Area,
+    Severity,
+    Urgency,
+    Certainty,
+    Issued,
+    Effective,
+    Onset,
+    Ends,
+    Expires,
+    Phenomena,
+    Responses,
+    Polygons,
+    MoreInformation,
+    Time,
+    Condition,
+    Temperature,
+    Precipitation,
+    Wind,
+    UvIndex,
+    NoPrecipitation,
+    StartingIn,
+    Minutes,
+    StoppingAt,
+    ForTheNextHour,
+    Chance,
+    Peak,
+}
+
+/// How heavily precipitation is falling, by the usual meteorological thresholds.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PrecipitationIntensity {
+    Light,
+    Moderate,
+    Heavy,
+}
+
+impl PrecipitationIntensity {
+    /// Classifies a precipitation rate, in millimetres per hour.
+    pub fn from_rate(rate: f64) -> Self {
+        if rate < 2.5 {
+            Self::Light
+        } else if rate < 7.6 {
+            Self::Moderate
+        } else {
+            Self::Heavy
+        }
+    }
+}
+
+/// The languages wetter's own text has been translated into.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Catalog {
+    English,
+    German,
+}
+
+impl Catalog {
+    /// Looks up the translation of a message.
+    pub const fn get(self, message: Message) -> &'static str {
+        match (self, message) {
+            (Self::English, Message::FeelsLike) => "Feels like",
+            (Self::English, Message::Humidity) => "Humidity",
+            (Self::English, Message::DewPoint) => "Dew point",
+            (Self::English, Message::NoAlerts) => "No weather alerts at",
+            (Self::English, Message::Source) => "Source",
+            (Self::English, Message::Area) => "Area",
+            (Self::English, Message::Severity) => "Severity",
+            (Self::English, Message::Urgency) => "Urgency",
+            (Self::English, Message::Certainty) => "Certainty",
+            (Self::English, Message::Issued) => "Issued",
+            (Self::English, Message::Effective) => "Effective",
+            (Self::English, Message::Onset) => "Onset",
+            (Self::English, Message::Ends) => "Ends",
+            (Self::English, Message::Expires) => "Expires",
+            (Self::English, Message::Phenomena) => "Phenomena",
+            (Self::English, Message::Responses) => "Recommended",
+            (Self::English, Message::Polygons) => "Polygons",
+            (Self::English, Message::MoreInformation) => "More information",
+            (Self::English, Message::Time) => "Time",
+            (Self::English, Message::Condition) => "Condition",
+            (Self::English, Message::Temperature) => "Temp/Feels",
+            (Self::English, Message::Precipitation) => "Precip.",
+            (Self::English, Message::Wind) => "Wind (gusts)",
+            (Self::English, Message::UvIndex) => "UV",
+            (Self::English, Message::NoPrecipitation) => "No precipitation for the next hour",
+            (Self::English, Message::StartingIn) => "starting in",
+            (Self::English, Message::Minutes) => "min",
+            (Self::English, Message::StoppingAt) => "stopping at",
+            (Self::English, Message::ForTheNextHour) => "for the next hour",
+            (Self::English, Message::Chance) => "Chance",
+            (Self::English, Message::Peak) => "Peak",
+            (Self::German, Message::FeelsLike) => "Gefühlt",
+            (Self::German, Message::Humidity) => "Luftfeuchte",
+            (Self::German, Message::DewPoint) => "Taupunkt",
+            (Self::German, Message::NoAlerts) => "Keine Unwetterwarnungen für",
+            (Self::German, Message::Source) => "Quelle",
+            (Self::German, Message::Area) => "Gebiet",
+            (Self::German, Message::Severity) => "Schwere",
+            (Self::German, Message::Urgency) => "Dringlichkeit",
+            (Self::German, Message::Certainty) => "Gewissheit",
+            (Self::German, Message::Issued) => "Ausgegeben",
+            (Self::German, Message::Effective) => "Gültig ab",
+            (Self::German, Message::Onset) => "Beginn",
+            (Self::German, Message::Ends) => "Ende",
+            (Self::German, Message::Expires) => "Gültig bis",
+            (Self::German, Message::Phenomena) => "Phänomene",
+            (Self::German, Message::Responses) => "Empfohlen",
+            (Self::German, Message::Polygons) => "Polygone",
+            (Self::German, Message::MoreInformation) => "Weitere Informationen",
+            (Self::German, Message::Time) => "Zeit",
+            (Self::German, Message::Condition) => "Wetter",
+            (Self::German, Message::Temperature) => "Temp./Gefühlt",
+            (Self::German, Message::Precipitation) => "Niederschl.",
+            (Self::German, Message::Wind) => "Wind (Böen)",
+            (Self::German, Message::UvIndex) => "UV",
+            (Self::German, Message::NoPrecipitation) => "Kein Niederschlag in der nächsten Stunde",
+            (Self::German, Message::StartingIn) => "in",
+            (Self::German, Message::Minutes) => "Min.",
+            (Self::German, Message::StoppingAt) => "bis",
+            (Self::German, Message::ForTheNextHour) => "in der nächsten Stunde",
+            (Self::German, Message::Chance) => "Wahrscheinlichkeit",
+            (Self::German, Message::Peak) => "Spitze",
+        }
+    }
+
+    /// Translates a WeatherKit condition code, e.g. `MostlyCloudy`. Condition codes aren't
+    /// localised by WeatherKit, so unknown codes are displayed as they are in English.
+    pub fn condition(self, code: &str) -> String {
+        let translation = match self {
+            Self::English => None,
+            Self::German => match code {
+                "Blizzard" => Some("Schneesturm"),
+                "BlowingDust" => Some("Staubsturm"),
+                "BlowingSnow" => Some("Schneetreiben"),
+                "Breezy" => Some("Leichter Wind"),
+                "Clear" => Some("Klar"),
+                "Cloudy" => Some("Bewölkt"),
+                "Drizzle" => Some("Nieselregen"),
+                "Flurries" => Some("Schneegestöber"),
+                "Foggy" => Some("Nebel"),
+                "FreezingDrizzle" => Some("Gefrierender Nieselregen"),
+                "FreezingRain" => Some("Gefrierender Regen"),
+                "Frigid" => Some("Eisig"),
+                "Hail" => Some("Hagel"),
+                "Haze" => Some("Dunst"),
+                "HeavyRain" => Some("Starkregen"),
+                "HeavySnow" => Some("Starker Schneefall"),
+                "Hot" => Some("Heiß"),
+                "Hurricane" => Some("Hurrikan"),
+                "IsolatedThunderstorms" => Some("Vereinzelte Gewitter"),
+                "MostlyClear" => Some("Überwiegend klar"),
+                "MostlyCloudy" => Some("Überwiegend bewölkt"),
+                "PartlyCloudy" => Some("Teilweise bewölkt"),
+                "Rain" => Some("Regen"),
+                "ScatteredThunderstorms" => Some("Verstreute Gewitter"),
+                "Sleet" => Some("Graupel"),
+                "Smoky" => Some("Rauch"),
+                "Snow" => Some("Schnee"),
+                "StrongStorms" => Some("Schwere Unwetter"),
+                "SunFlurries" => Some("Sonne und Schneeschauer"),
+                "SunShowers" => Some("Sonne und Regenschauer"),
+                "Thunderstorms" => Some("Gewitter"),
+                "TropicalStorm" => Some("Tropensturm"),
+                "Windy" => Some("Windig"),
+                "WintryMix" => Some("Schneeregen"),
+                _ => None,
+            },
+        };
+
+        match translation {
+            Some(translation) => translation.to_owned(),
+            None => code.to_title_case(),
+        }
+    }
+
+    /// Names precipitation of the given intensity and type, e.g. "Light rain".
+    pub fn precipitation(
+        self,
+        intensity: PrecipitationIntensity,
+        kind: PrecipitationType,
+    ) -> String {
+        let adjective = match (self, intensity) {
+            (Self::English, PrecipitationIntensity::Light) => "Light",
+            (Self::English, PrecipitationIntensity::Moderate) => "Moderate",
+            (Self::English, PrecipitationIntensity::Heavy) => "Heavy",
+            // All the German nouns below are masculine
+            (Self::German, PrecipitationIntensity::Light) => "Leichter",
+            (Self::German, PrecipitationIntensity::Moderate) => "Mäßiger",
+            (Self::German, PrecipitationIntensity::Heavy) => "Starker",
+        };
+        let noun = match (self, kind) {
+            (Self::English, PrecipitationType::Clear) => "clear",
+            (Self::English, PrecipitationType::Precipitation) => "precipitation",
+            (Self::English, PrecipitationType::Rain) => "rain",
+            (Self::English, PrecipitationType::Snow) => "snow",
+            (Self::English, PrecipitationType::Sleet) => "sleet",
+            (Self::English, PrecipitationType::Hail) => "hail",
+            (Self::English, PrecipitationType::Mixed) => "wintry mix",
+            (Self::German, PrecipitationType::Clear) => "Klar",
+            (Self::German, PrecipitationType::Precipitation) => "Niederschlag",
+            (Self::German, PrecipitationType::Rain) => "Regen",
+            (Self::German, PrecipitationType::Snow) => "Schneefall",
+            (Self::German, PrecipitationType::Sleet) => "Graupel",
+            (Self::German, PrecipitationType::Hail) => "Hagel",
+            (Self::German, PrecipitationType::Mixed) => "Schneeregen",
+        };
+
+        format!("{} {}", adjective, noun)
+    }
+
+    /// The name of an alert's severity.
+    pub const fn severity(self, severity: Severity) -> &'static str {
+        match (self, severity) {
+            (Self::English, Severity::Extreme) => "Extreme",
+            (Self::English, Severity::Severe) => "Severe",
+            (Self::English, Severity::Moderate) => "Moderate",
+            (Self::English, Severity::Minor) => "Minor",
+            (Self::English, Severity::Unknown) => "Unknown",
+            (Self::German, Severity::Extreme) => "Extrem",
+            (Self::German, Severity::Severe) => "Schwer",
+            (Self::German, Severity::Moderate) => "Mäßig",
+            (Self::German, Severity::Minor) => "Gering",
+            (Self::German, Severity::Unknown) => "Unbekannt",
+        }
+    }
+
+    /// The name of an alert's urgency.
+    pub const fn urgency(self, urgency: Urgency) -> &'static str {
+        match (self, urgency) {
+            (Self::English, Urgency::Immediate) => "Immediate",
+            (Self::English, Urgency::Expected) => "Expected",
+            (Self::English, Urgency::Future) => "Future",
+            (Self::English, Urgency::Past) => "Past",
+            (Self::English, Urgency::Unknown) => "Unknown",
+            (Self::German, Urgency::Immediate) => "Sofort",
+            (Self::German, Urgency::Expected) => "Erwartet",
+            (Self::German, Urgency::Future) => "Zukünftig",
+            (Self::German, Urgency::Past) => "Vergangen",
+            (Self::German, Urgency::Unknown) => "Unbekannt",
+        }
+    }
+
+    /// The name of an alert's certainty.
+    pub const fn certainty(self, certainty: Certainty) -> &'static str {
+        match (self, certainty) {
+            (Self::English, Certainty::Observed) => "Observed",
+            (Self::English, Certainty::Likely) => "Likely",
+            (Self::English, Certainty::Possible) => "Possible",
+            (Self::English, Certainty::Unlikely) => "Unlikely",
+            (Self::English, Certainty::Unknown) => "Unknown",
+            (Self::German, Certainty::Observed) => "Beobachtet",
+            (Self::German, Certainty::Likely) => "Wahrscheinlich",
+            (Self::German, Certainty::Possible) => "Möglich",
+            (Self::German, Certainty::Unlikely) => "Unwahrscheinlich",
+            (Self::German, Certainty::Unknown) => "Unbekannt",
+        }
+    }
+
+    /// The description of an action recommended by an alert.
+    pub const fn response(self, response: ResponseType) -> &'static str {
+        match (self, response) {
+            (Self::English, ResponseType::Shelter) => "Take shelter in place",
+            (Self::English, ResponseType::Evacuate) => "Evacuate",
+            (Self::English, ResponseType::Prepare) => "Make preparations",
+            (Self::English, ResponseType::Execute) => "Execute pre-planned activity",
+            (Self::English, ResponseType::Avoid) => "Avoid the event",
+            (Self::English, ResponseType::Monitor) => "Monitor the situation",
+            (Self::English, ResponseType::Assess) => "Assess the situation",
+            (Self::English, ResponseType::AllClear) => "All clear",
+            (Self::English, ResponseType::None) => "No action",
+            (Self::German, ResponseType::Shelter) => "Schutz suchen",
+            (Self::German, ResponseType::Evacuate) => "Evakuieren",
+            (Self::German, ResponseType::Prepare) => "Vorbereitungen treffen",
+            (Self::German, ResponseType::Execute) => "Geplante Maßnahmen ausführen",
+            (Self::German, ResponseType::Avoid) => "Das Ereignis meiden",
+            (Self::German, ResponseType::Monitor) => "Die Lage beobachten",
+            (Self::German, ResponseType::Assess) => "Die Lage einschätzen",
+            (Self::German, ResponseType::AllClear) => "Entwarnung",
+            (Self::German, ResponseType::None) => "Keine Maßnahmen",
+        }
+    }
+
+    /// Formats a date with the abbreviated names of its weekday and month, e.g. `Fri, Oct 16`.
+    pub fn short_date(self, date: NaiveDate) -> String {
+        match self {
+            Self::English => date.format("%a, %b %d").to_string(),
+            Self::German => {
+                const MONTHS: [&str; 12] = [
+                    "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sep.", "Okt.",
+                    "Nov.", "Dez.",
+                ];
+                let weekday = match date.weekday() {
+                    Weekday::Mon => "Mo",
+                    Weekday::Tue => "Di",
+                    Weekday::Wed => "Mi",
+                    Weekday::Thu => "Do",
+                    Weekday::Fri => "Fr",
+                    Weekday::Sat => "Sa",
+                    Weekday::Sun => "So",
+                };
+
+                format!(
+                    "{}., {}. {}",
+                    weekday,
+                    date.day(),
+                    MONTHS[date.month0() as usize]
+                )
+            }
+        }
+    }
+}
@@ -1,15 +1,33 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::app::App;
+use crate::app::{App, WeatherkitRequestError};
+use std::process::ExitCode;
 
 mod app;
 mod auth;
+mod cache;
 mod cli;
+mod config;
+mod geocoding;
 mod geolocation;
+mod i18n;
+mod metrics;
+mod output;
+mod retry;
 mod tui;
+mod units;
 mod weatherkit;
+mod window;
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    App::run().await
+async fn main() -> ExitCode {
+    match App::run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            e.chain()
+                .find_map(|x| x.downcast_ref::<WeatherkitRequestError>())
+                .map_or(ExitCode::FAILURE, |x| ExitCode::from(x.exit_code()))
+        }
+    }
 }
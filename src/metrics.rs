@@ -0,0 +1,505 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::Location;
+use crate::units::{Quantity, Speed, SpeedUnit};
+use crate::weatherkit::{CurrentWeather, HourWeatherConditions, Weather};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+const METRICS_PATH: &str = "/metrics";
+/// The most of a request that is read, which is plenty for the request line and headers of a
+/// scrape.
+const MAX_REQUEST_LENGTH: usize = 8192;
+
+/// The latest weather, shared between the task refreshing it and the server.
+#[derive(Default)]
+pub struct Snapshot {
+    pub weather: Weather,
+    pub refreshed: Option<DateTime<Utc>>,
+}
+
+pub type SharedSnapshot = Arc<Mutex<Snapshot>>;
+
+/// A value exported as a gauge, which may be missing from the response.
+struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    value: Option<f64>,
+}
+
+impl Gauge {
+    fn new(name: &'static str, help: &'static str, value: impl Into<Option<f64>>) -> Self {
+        Self {
+            name,
+            help,
+            value: value.into(),
+        }
+    }
+}
+
+/// Speeds are exported in metres per second, the base unit Prometheus recommends.
+fn metres_per_second(speed: f64) -> f64 {
+    Speed(speed).convert(SpeedUnit::MetresPerSecond)
+}
+
+fn current_gauges(current: &CurrentWeather) -> Vec<Gauge> {
+    vec![
+        Gauge::new(
+            "temperature_celsius",
+            "The temperature.",
+            current.temperature,
+        ),
+        Gauge::new(
+            "apparent_temperature_celsius",
+            "The feels-like temperature when factoring wind and humidity.",
+            current.temperature_apparent,
+        ),
+        Gauge::new(
+            "dew_point_celsius",
+            "The temperature at which relative humidity is 100%.",
+            current.temperature_dew_point,
+        ),
+        Gauge::new(
+            "relative_humidity_ratio",
+            "The relative humidity, from 0 to 1.",
+            current.humidity,
+        ),
+        Gauge::new(
+            "cloud_cover_ratio",
+            "The fraction of the sky covered with clouds, from 0 to 1.",
+            current.cloud_cover,
+        ),
+        Gauge::new(
+            "pressure_hectopascals",
+            "The sea level air pressure.",
+            current.pressure,
+        ),
+        Gauge::new(
+            "precipitation_intensity_millimetres_per_hour",
+            "The precipitation intensity.",
+            current.precipitation_intensity,
+        ),
+        Gauge::new(
+            "wind_speed_metres_per_second",
+            "The wind speed.",
+            metres_per_second(current.wind_speed),
+        ),
+        Gauge::new(
+            "wind_gust_metres_per_second",
+            "The maximum wind gust speed.",
+            current.wind_gust.map(metres_per_second),
+        ),
+        Gauge::new(
+            "wind_direction_degrees",
+            "The direction the wind is blowing from.",
+            current.wind_direction.map(f64::from),
+        ),
+        Gauge::new(
+            "uv_index",
+            "The level of ultraviolet radiation.",
+            f64::from(current.uv_index),
+        ),
+        Gauge::new(
+            "visibility_metres",
+            "The distance at which terrain is visible.",
+            current.visibility,
+        ),
+    ]
+}
+
+fn hour_gauges(hour: &HourWeatherConditions) -> Vec<Gauge> {
+    vec![
+        Gauge::new(
+            "temperature_celsius",
+            "The forecast temperature at the start of the hour.",
+            hour.temperature,
+        ),
+        Gauge::new(
+            "apparent_temperature_celsius",
+            "The forecast feels-like temperature at the start of the hour.",
+            hour.temperature_apparent,
+        ),
+        Gauge::new(
+            "relative_humidity_ratio",
+            "The forecast relative humidity at the start of the hour, from 0 to 1.",
+            hour.humidity,
+        ),
+        Gauge::new(
+            "cloud_cover_ratio",
+            "The forecast fraction of the sky covered with clouds, from 0 to 1.",
+            hour.cloud_cover,
+        ),
+        Gauge::new(
+            "precipitation_chance_ratio",
+            "The chance of precipitation during the hour, from 0 to 1.",
+            hour.precipitation_chance,
+        ),
+        Gauge::new(
+            "precipitation_amount_millimetres",
+            "The forecast amount of precipitation during the hour.",
+            hour.precipitation_amount,
+        ),
+        Gauge::new(
+            "pressure_hectopascals",
+            "The forecast sea level air pressure at the start of the hour.",
+            hour.pressure,
+        ),
+        Gauge::new(
+            "wind_speed_metres_per_second",
+            "The forecast wind speed at the start of the hour.",
+            metres_per_second(hour.wind_speed),
+        ),
+        Gauge::new(
+            "uv_index",
+            "The forecast level of ultraviolet radiation at the start of the hour.",
+            f64::from(hour.uv_index),
+        ),
+    ]
+}
+
+/// Escapes a label value for the exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Metrics in the Prometheus text exposition format, which is also valid OpenMetrics as long as
+/// only gauges are used.
+struct Exposition {
+    contents: String,
+    labels: String,
+}
+
+impl Exposition {
+    fn new(location: &Location) -> Self {
+        Self {
+            contents: String::new(),
+            labels: format!(
+                "location=\"{}\",latitude=\"{}\",longitude=\"{}\"",
+                escape(&location.to_string()),
+                location.lat,
+                location.lon
+            ),
+        }
+    }
+
+    fn family(&mut self, name: &str, help: &str) {
+        let _ = writeln!(self.contents, "# HELP wetter_{} {}", name, help);
+        let _ = writeln!(self.contents, "# TYPE wetter_{} gauge", name);
+    }
+
+    fn sample(&mut self, name: &str, extra_labels: &str, value: f64) {
+        let _ = writeln!(
+            self.contents,
+            "wetter_{}{{{}{}}} {}",
+            name, self.labels, extra_labels, value
+        );
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, help);
+        self.sample(name, "", value);
+    }
+}
+
+/// Renders the metrics of a snapshot. Forecast hours are labelled by how many hours ahead of
+/// now they are, so the forecast for the same hour moves between series as time passes.
+pub fn render(
+    snapshot: &Snapshot,
+    location: &Location,
+    hours: usize,
+    now: DateTime<Utc>,
+) -> String {
+    let mut exposition = Exposition::new(location);
+
+    if let Some(refreshed) = snapshot.refreshed {
+        exposition.gauge(
+            "last_refresh_timestamp_seconds",
+            "When the weather was last downloaded or read from the cache.",
+            refreshed.timestamp() as f64,
+        );
+    }
+
+    if let Some(current) = &snapshot.weather.current_weather {
+        exposition.gauge(
+            "current_as_of_timestamp_seconds",
+            "The time the current conditions are for.",
+            current.as_of.timestamp() as f64,
+        );
+        exposition.gauge(
+            "current_expiry_timestamp_seconds",
+            "The time the current conditions are no longer valid.",
+            current.metadata.expire_time.timestamp() as f64,
+        );
+
+        for gauge in current_gauges(current) {
+            let name = format!("current_{}", gauge.name);
+            exposition.family(&name, gauge.help);
+            if let Some(value) = gauge.value {
+                exposition.sample(&name, "", value);
+            }
+        }
+    }
+
+    if let Some(hourly) = &snapshot.weather.forecast_hourly {
+        let upcoming = hourly
+            .hours
+            .iter()
+            .filter(|x| x.forecast_start > now)
+            .take(hours)
+            .map(hour_gauges)
+            .collect::<Vec<_>>();
+
+        if let Some(first) = upcoming.first() {
+            for (index, gauge) in first.iter().enumerate() {
+                let name = format!("forecast_{}", gauge.name);
+                exposition.family(&name, gauge.help);
+                for (hours_ahead, gauges) in upcoming.iter().enumerate() {
+                    if let Some(value) = gauges[index].value {
+                        let labels = format!(",hours_ahead=\"{}\"", hours_ahead + 1);
+                        exposition.sample(&name, &labels, value);
+                    }
+                }
+            }
+        }
+    }
+
+    exposition.contents
+}
+
+/// Serves the metrics of the latest snapshot over HTTP until the listener fails.
+pub async fn serve(
+    listener: TcpListener,
+    snapshot: SharedSnapshot,
+    location: Location,
+    hours: usize,
+) -> anyhow::Result<()> {
+    let location = Arc::new(location);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let snapshot = snapshot.clone();
+        let location = location.clone();
+
+        tokio::spawn(async move {
+            // A scraper that goes away mid-request is its own problem
+            let _ = respond(stream, &snapshot, &location, hours).await;
+        });
+    }
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    snapshot: &SharedSnapshot,
+    location: &Location,
+    hours: usize,
+) -> anyhow::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|x| x == b"\r\n\r\n") && request.len() < MAX_REQUEST_LENGTH {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut lines = request.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line
+        .next()
+        .unwrap_or_default()
+        .split('?')
+        .next()
+        .unwrap_or_default();
+    let openmetrics = lines.any(|x| {
+        x.to_ascii_lowercase().starts_with("accept:") && x.contains("application/openmetrics-text")
+    });
+
+    let (status, content_type, body) = if method != "GET" && method != "HEAD" {
+        ("405 Method Not Allowed", "text/plain", String::new())
+    } else if path != METRICS_PATH {
+        (
+            "404 Not Found",
+            "text/plain",
+            format!("Metrics are served at {}\n", METRICS_PATH),
+        )
+    } else {
+        let snapshot = snapshot
+            .lock()
+            .map_err(|_| anyhow!("The snapshot is poisoned"))?;
+        if snapshot.refreshed.is_none() {
+            (
+                "503 Service Unavailable",
+                "text/plain",
+                "The weather has not been downloaded yet\n".to_owned(),
+            )
+        } else if openmetrics {
+            let mut body = render(&snapshot, location, hours, Utc::now());
+            body.push_str("# EOF\n");
+            ("200 OK", OPENMETRICS_CONTENT_TYPE, body)
+        } else {
+            (
+                "200 OK",
+                PROMETHEUS_CONTENT_TYPE,
+                render(&snapshot, location, hours, Utc::now()),
+            )
+        }
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weatherkit::fixture;
+
+    fn location() -> Location {
+        Location {
+            country: String::new(),
+            country_code: "DE".to_owned(),
+            city: "Berlin \"Mitte\" \\ Nord".to_owned(),
+            lat: 52.52,
+            lon: 13.41,
+            timezone: "Europe/Berlin".to_owned(),
+        }
+    }
+
+    fn time(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    const LABELS: &str =
+        r#"location="Berlin \"Mitte\" \\ Nord, DE",latitude="52.52",longitude="13.41""#;
+
+    #[test]
+    fn renders_current_weather() {
+        let snapshot = Snapshot {
+            weather: fixture(),
+            refreshed: Some(time("2026-10-17T06:05:00Z")),
+        };
+
+        let metrics = render(&snapshot, &location(), 1, time("2026-10-17T06:30:00Z"));
+
+        assert!(metrics.contains(&format!(
+            "# HELP wetter_current_temperature_celsius The temperature.\n\
+             # TYPE wetter_current_temperature_celsius gauge\n\
+             wetter_current_temperature_celsius{{{}}} 9.4\n",
+            LABELS
+        )));
+        assert!(metrics.contains(&format!(
+            "wetter_last_refresh_timestamp_seconds{{{}}} 1792217100\n",
+            LABELS
+        )));
+        // Speeds are converted from kilometres per hour
+        assert!(metrics.contains(&format!(
+            "wetter_current_wind_speed_metres_per_second{{{}}} {}\n",
+            LABELS,
+            15.2 / 3.6
+        )));
+    }
+
+    #[test]
+    fn renders_upcoming_hours() {
+        let snapshot = Snapshot {
+            weather: fixture(),
+            refreshed: Some(time("2026-10-17T06:05:00Z")),
+        };
+
+        let metrics = render(&snapshot, &location(), 1, time("2026-10-17T06:30:00Z"));
+        let samples = metrics
+            .lines()
+            .filter(|x| x.starts_with("wetter_forecast_temperature_celsius{"))
+            .collect::<Vec<_>>();
+
+        // The hour that has started already is left out, and only as many hours as asked for
+        // are included
+        assert_eq!(
+            samples,
+            [format!(
+                "wetter_forecast_temperature_celsius{{{},hours_ahead=\"1\"}} 8.5",
+                LABELS
+            )]
+        );
+    }
+
+    #[test]
+    fn leaves_out_missing_values() {
+        let mut weather = fixture();
+        if let Some(current) = &mut weather.current_weather {
+            current.wind_gust = None;
+        }
+        let snapshot = Snapshot {
+            weather,
+            refreshed: Some(time("2026-10-17T06:05:00Z")),
+        };
+
+        let metrics = render(&snapshot, &location(), 1, time("2026-10-17T06:30:00Z"));
+
+        assert!(metrics.contains("# TYPE wetter_current_wind_gust_metres_per_second gauge\n"));
+        assert!(!metrics.contains("wetter_current_wind_gust_metres_per_second{"));
+    }
+
+    async fn get(address: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn serves_metrics_once_refreshed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let snapshot = SharedSnapshot::default();
+        tokio::spawn(serve(listener, snapshot.clone(), location(), 1));
+
+        assert!(get(address, "/metrics")
+            .await
+            .starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+
+        *snapshot.lock().unwrap() = Snapshot {
+            weather: fixture(),
+            refreshed: Some(Utc::now()),
+        };
+
+        let response = get(address, "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(&format!("Content-Type: {}\r\n", PROMETHEUS_CONTENT_TYPE)));
+        assert!(response.contains("wetter_current_temperature_celsius{"));
+
+        assert!(get(address, "/")
+            .await
+            .starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
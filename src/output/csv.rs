@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::output::series::Series;
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// Formats a value as a CSV field, quoting it if needed as described in RFC 4180.
+fn field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Writes a series as CSV, with a header row.
+pub fn write(series: &Series) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", series.headers.join(","))?;
+    for row in &series.rows {
+        writeln!(
+            stdout,
+            "{}",
+            row.iter().map(field).collect::<Vec<_>>().join(",")
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::Location;
+use crate::output::series::Series;
+use serde_json::Value;
+use std::io::{self, Write};
+
+const PREFIX: &str = "wetter";
+
+/// Turns text into a single node of a metric path, as Graphite splits paths at dots.
+fn node(text: &str) -> String {
+    text.split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_ascii_lowercase()
+}
+
+/// Graphite only stores numbers, so flags are written as 0 or 1 and text is left out.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Bool(value) => Some(f64::from(u8::from(*value))),
+        Value::Number(number) => number.as_f64(),
+        _ => None,
+    }
+}
+
+/// Writes a series in Graphite's plaintext protocol, with metric paths of the form
+/// `wetter.<location>.<series>.<column>` and timestamps in seconds.
+pub fn write(series: &Series, location: &Location) -> anyhow::Result<()> {
+    let prefix = format!("{}.{}.{}", PREFIX, node(&location.to_string()), series.name);
+
+    let mut stdout = io::stdout().lock();
+    for (time, row) in series.times.iter().zip(&series.rows) {
+        for (header, value) in series.headers.iter().zip(row) {
+            if let Some(value) = number(value) {
+                writeln!(
+                    stdout,
+                    "{}.{} {} {}",
+                    prefix,
+                    node(header),
+                    value,
+                    time.timestamp()
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::cli::Forecast;
+use crate::geolocation::Location;
+use crate::i18n::{Catalog, Message};
+use crate::units::{Length, Temperature, Units};
+use crate::weatherkit::{DayWeatherConditions, Weather, WeatherAlertSummary};
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::io::{self, Write};
+
+/// The longest a line may be, in octets, before it has to be folded (RFC 5545, section 3.1).
+const MAX_LINE_LENGTH: usize = 75;
+const DATE_FORMAT: &str = "%Y%m%d";
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// How often calendar applications subscribed to the feed should fetch it again.
+const REFRESH_INTERVAL: &str = "PT1H";
+
+/// Escapes text for use in a property value (RFC 5545, section 3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// An iCalendar document, built up one content line at a time.
+#[derive(Default)]
+struct Calendar {
+    contents: String,
+}
+
+impl Calendar {
+    /// Appends a content line, folding it so that no line is longer than allowed.
+    fn line(&mut self, name: &str, value: &str) {
+        let mut length = 0;
+        for c in name.chars().chain([':']).chain(value.chars()) {
+            if length + c.len_utf8() > MAX_LINE_LENGTH {
+                self.contents.push_str("\r\n ");
+                length = 1;
+            }
+            self.contents.push(c);
+            length += c.len_utf8();
+        }
+        self.contents.push_str("\r\n");
+    }
+
+    fn text(&mut self, name: &str, text: &str) {
+        self.line(name, &escape(text));
+    }
+
+    fn day(
+        &mut self,
+        day: &DayWeatherConditions,
+        location: &Location,
+        timezone: Tz,
+        units: &Units,
+        catalog: Catalog,
+        stamp: &str,
+    ) {
+        let date = day.forecast_start.with_timezone(&timezone).date_naive();
+        let Some(next_date) = date.succ_opt() else {
+            return;
+        };
+
+        let precipitation = format!(
+            "{}: {} ({} {:.0}%)",
+            catalog.get(Message::Precipitation),
+            units.format(Length(day.precipitation_amount)),
+            catalog.get(Message::Chance),
+            day.precipitation_chance * 100.0
+        );
+        let uv_index = format!("{}: {}", catalog.get(Message::UvIndex), day.max_uv_index);
+
+        self.line("BEGIN", "VEVENT");
+        self.text(
+            "UID",
+            &format!(
+                "day-{}-{}-{}@wetter",
+                date.format(DATE_FORMAT),
+                location.lat,
+                location.lon
+            ),
+        );
+        self.line("DTSTAMP", stamp);
+        self.line("DTSTART;VALUE=DATE", &date.format(DATE_FORMAT).to_string());
+        self.line(
+            "DTEND;VALUE=DATE",
+            &next_date.format(DATE_FORMAT).to_string(),
+        );
+        self.text(
+            "SUMMARY",
+            &format!(
+                "{}, {:.0}º/{:.0}º",
+                catalog.condition(&day.condition_code),
+                units.value(Temperature(day.temperature_max)),
+                units.value(Temperature(day.temperature_min))
+            ),
+        );
+        self.text("DESCRIPTION", &format!("{}\n{}", precipitation, uv_index));
+        self.line("TRANSP", "TRANSPARENT");
+        self.line("END", "VEVENT");
+    }
+
+    fn alert(&mut self, alert: &WeatherAlertSummary, timezone: Tz, catalog: Catalog, stamp: &str) {
+        let mut description = alert
+            .fields(timezone, catalog)
+            .into_iter()
+            .filter_map(|(message, value)| Some(format!("{}: {}", catalog.get(message), value?)))
+            .collect::<Vec<_>>();
+        if let Some(details_url) = &alert.details_url {
+            description.push(format!(
+                "{}: {}",
+                catalog.get(Message::MoreInformation),
+                details_url
+            ));
+        }
+
+        self.line("BEGIN", "VEVENT");
+        self.text("UID", &format!("alert-{}@wetter", alert.id));
+        self.line("DTSTAMP", stamp);
+        self.line("DTSTART", &format_time(alert.effective_time));
+        self.line("DTEND", &format_time(alert.expire_time));
+        self.text("SUMMARY", &alert.description);
+        self.text("DESCRIPTION", &description.join("\n"));
+        if let Some(details_url) = &alert.details_url {
+            self.line("URL", details_url);
+        }
+        self.line("TRANSP", "TRANSPARENT");
+        self.line("END", "VEVENT");
+    }
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format(DATE_TIME_FORMAT).to_string()
+}
+
+/// Writes the days of the weekly forecast as all-day events and the alerts as events lasting
+/// until they expire, or both for the calendar, as an iCalendar feed that calendar
+/// applications can subscribe to. Times in the descriptions of alerts are in the location's
+/// timezone, like the dates of the days.
+pub fn write(
+    weather: &Weather,
+    forecast: Forecast,
+    location: &Location,
+    units: &Units,
+    catalog: Catalog,
+) -> anyhow::Result<()> {
+    let calendar = render(weather, forecast, location, units, catalog, Utc::now())?;
+    io::stdout().lock().write_all(calendar.as_bytes())?;
+
+    Ok(())
+}
+
+/// Builds the feed of the given forecast, leaving out any other datasets a cached response may
+/// include.
+fn render(
+    weather: &Weather,
+    forecast: Forecast,
+    location: &Location,
+    units: &Units,
+    catalog: Catalog,
+    now: DateTime<Utc>,
+) -> anyhow::Result<String> {
+    // No alerts in the response just means there are none to add
+    let (days, alerts) = match forecast {
+        Forecast::Weekly if weather.forecast_daily.is_none() => bail!(
+            "Weekly weather for location {} was requested but is not available!",
+            location
+        ),
+        Forecast::Calendar
+            if weather.forecast_daily.is_none() && weather.weather_alerts.is_none() =>
+        {
+            bail!(
+                "The calendar for location {} was requested but neither the daily forecast \
+                 nor alerts are available!",
+                location
+            )
+        }
+        Forecast::Weekly => (true, false),
+        Forecast::Alerts => (false, true),
+        Forecast::Calendar => (true, true),
+        _ => bail!(
+            "Only the weekly forecast, alerts and the calendar can be written as an iCalendar \
+             feed."
+        ),
+    };
+
+    let timezone = location.tz()?;
+    let stamp = format_time(now);
+    let mut calendar = Calendar::default();
+
+    calendar.line("BEGIN", "VCALENDAR");
+    calendar.line("VERSION", "2.0");
+    calendar.line(
+        "PRODID",
+        &format!("-//wetter//wetter {}//EN", env!("CARGO_PKG_VERSION")),
+    );
+    calendar.line("CALSCALE", "GREGORIAN");
+    calendar.line("METHOD", "PUBLISH");
+    calendar.text("X-WR-CALNAME", &format!("wetter: {}", location));
+    calendar.line("REFRESH-INTERVAL;VALUE=DURATION", REFRESH_INTERVAL);
+    calendar.line("X-PUBLISHED-TTL", REFRESH_INTERVAL);
+
+    if days {
+        for day in weather.forecast_daily.iter().flat_map(|x| &x.days) {
+            calendar.day(day, location, timezone, units, catalog, &stamp);
+        }
+    }
+    if alerts {
+        for alert in weather.weather_alerts.iter().flat_map(|x| &x.alerts) {
+            calendar.alert(alert, timezone, catalog, &stamp);
+        }
+    }
+
+    calendar.line("END", "VCALENDAR");
+
+    Ok(calendar.contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+    use crate::units::UnitSystem;
+    use crate::weatherkit::fixture;
+
+    fn render_fixture(forecast: Forecast) -> anyhow::Result<String> {
+        let location = Location {
+            country: String::new(),
+            country_code: "DE".to_owned(),
+            city: "Berlin".to_owned(),
+            lat: 52.52,
+            lon: 13.41,
+            timezone: "Europe/Berlin".to_owned(),
+        };
+        render(
+            &fixture(),
+            forecast,
+            &location,
+            &Units::from(UnitSystem::Metric),
+            Language::default().catalog(),
+            DateTime::parse_from_rfc3339("2026-10-17T06:30:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+    }
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(escape("a,b;c\\d\ne\r\nf"), r"a\,b\;c\\d\ne\nf");
+    }
+
+    #[test]
+    fn folds_long_lines_between_characters() {
+        let mut calendar = Calendar::default();
+        calendar.line("SUMMARY", &"º".repeat(40));
+
+        let lines = calendar
+            .contents
+            .strip_suffix("\r\n")
+            .unwrap()
+            .split("\r\n")
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        // "SUMMARY:" and 33 two-octet characters fit in 74 octets, one short of the limit
+        assert_eq!(lines[0], format!("SUMMARY:{}", "º".repeat(33)));
+        assert_eq!(lines[1], format!(" {}", "º".repeat(7)));
+        assert!(lines.iter().all(|x| x.len() <= MAX_LINE_LENGTH));
+    }
+
+    #[test]
+    fn writes_days_as_dates_and_alerts_as_times() {
+        let calendar = render_fixture(Forecast::Calendar).unwrap();
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:20261017\r\nDTEND;VALUE=DATE:20261018\r\n"));
+        assert!(calendar.contains("DTSTART:20261017T060000Z\r\nDTEND:20261017T180000Z\r\n"));
+        assert!(calendar.contains("SUMMARY:Thunderstorms\\, heavy rain\\; hail\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 4);
+    }
+
+    #[test]
+    fn writes_only_the_events_asked_for() {
+        let weekly = render_fixture(Forecast::Weekly).unwrap();
+        assert_eq!(weekly.matches("BEGIN:VEVENT").count(), 2);
+        assert!(!weekly.contains("UID:alert-"));
+
+        let alerts = render_fixture(Forecast::Alerts).unwrap();
+        assert_eq!(alerts.matches("BEGIN:VEVENT").count(), 2);
+        assert!(!alerts.contains("UID:day-"));
+
+        assert!(render_fixture(Forecast::Hourly).is_err());
+    }
+}
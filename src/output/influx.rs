@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::geolocation::Location;
+use crate::output::series::Series;
+use anyhow::anyhow;
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// Escapes a measurement name, tag key, tag value or field key.
+fn escape_key(key: &str) -> String {
+    key.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+/// Formats a value as a field value, or returns `None` if the value is missing.
+fn field_value(value: &Value) -> Option<String> {
+    Some(match value {
+        Value::Null | Value::Array(_) | Value::Object(_) => return None,
+        Value::Bool(value) => value.to_string(),
+        Value::Number(number) if number.is_f64() => number.to_string(),
+        Value::Number(number) => format!("{}i", number),
+        Value::String(text) => format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"")),
+    })
+}
+
+/// Writes a series in the InfluxDB line protocol, with one point per row timestamped in
+/// nanoseconds and tagged with the location.
+pub fn write(series: &Series, location: &Location) -> anyhow::Result<()> {
+    let measurement = format!(
+        "{},location={},latitude={},longitude={}",
+        escape_key(&format!("wetter_{}", series.name)),
+        escape_key(&location.to_string()),
+        location.lat,
+        location.lon
+    );
+
+    let mut stdout = io::stdout().lock();
+    for (time, row) in series.times.iter().zip(&series.rows) {
+        let fields = series
+            .headers
+            .iter()
+            .zip(row)
+            .filter_map(|(header, value)| {
+                Some(format!("{}={}", escape_key(header), field_value(value)?))
+            })
+            .collect::<Vec<_>>();
+
+        // A point needs at least one field
+        if fields.is_empty() {
+            continue;
+        }
+
+        let timestamp = time
+            .timestamp_nanos_opt()
+            .ok_or_else(|| anyhow!("{} can't be represented in nanoseconds", time))?;
+        writeln!(stdout, "{} {} {}", measurement, fields.join(","), timestamp)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::cli::Forecast;
+use crate::geolocation::Location;
+use crate::output::{normalise, unit_symbols};
+use crate::units::Units;
+use crate::weatherkit::{Weather, WeatherAlert};
+use anyhow::Context;
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+fn print(document: &Value) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, document)?;
+    writeln!(stdout)?;
+
+    Ok(())
+}
+
+/// Builds the document wrapping the output, with the units and, if known, the location it's
+/// for.
+fn document<T: Serialize>(
+    key: &str,
+    value: &T,
+    location: Option<&Location>,
+    timezone: Tz,
+    units: &Units,
+) -> anyhow::Result<Value> {
+    let mut value = serde_json::to_value(value).context("Could not serialise the weather")?;
+    normalise(&mut value, units, timezone);
+
+    let mut document = json!({
+        "timezone": timezone.name(),
+        "units": unit_symbols(units),
+    });
+    if let Some(location) = location {
+        document["location"] = json!(location);
+    }
+    document[key] = value;
+
+    Ok(document)
+}
+
+/// Writes the datasets of a forecast as a JSON document, keyed by the names WeatherKit gives
+/// the datasets.
+pub fn write(
+    mut weather: Weather,
+    forecast: Forecast,
+    location: &Location,
+    timezone: Tz,
+    units: &Units,
+    hours: Option<usize>,
+) -> anyhow::Result<()> {
+    if let (Some(forecast_hourly), Some(hours)) = (&mut weather.forecast_hourly, hours) {
+        forecast_hourly.hours.truncate(hours);
+    }
+
+    let mut document = document("weather", &weather, Some(location), timezone, units)?;
+
+    // A cached response may include the datasets of other forecasts too
+    let datasets = forecast.datasets();
+    if let Some(weather) = document["weather"].as_object_mut() {
+        weather.retain(|key, value| {
+            !value.is_null() && datasets.iter().any(|dataset| dataset.fmt() == key)
+        });
+    }
+
+    print(&document)
+}
+
+/// Writes the full details of an alert as a JSON document.
+pub fn write_alert(alert: &WeatherAlert, timezone: Tz, units: &Units) -> anyhow::Result<()> {
+    print(&document("alert", alert, None, timezone, units)?)
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+pub mod csv;
+pub mod graphite;
+pub mod ics;
+pub mod influx;
+pub mod json;
+pub mod ndjson;
+pub mod series;
+
+use crate::units::{
+    Length, PrecipitationRate, Pressure, Quantity, Speed, Temperature, Unit, Units,
+};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde_json::{json, Value};
+
+/// A quantity held by fields of the WeatherKit model.
+#[derive(Copy, Clone, Debug)]
+enum Field {
+    Temperature,
+    Speed,
+    Pressure,
+    Length,
+    PrecipitationRate,
+}
+
+impl Field {
+    /// Identifies the quantity a field holds by its name in WeatherKit's responses.
+    fn of(name: &str) -> Option<Self> {
+        Some(match name {
+            "temperature"
+            | "temperatureApparent"
+            | "temperatureDewPoint"
+            | "temperatureMax"
+            | "temperatureMin" => Self::Temperature,
+            "windSpeed" | "windGust" => Self::Speed,
+            "pressure" => Self::Pressure,
+            "precipitationAmount" | "snowfallAmount" => Self::Length,
+            "precipitationIntensity" | "snowfallIntensity" => Self::PrecipitationRate,
+            _ => return None,
+        })
+    }
+
+    /// Converts a value of the quantity to the chosen units.
+    fn convert(self, value: f64, units: &Units) -> f64 {
+        match self {
+            Self::Temperature => units.value(Temperature(value)),
+            Self::Speed => units.value(Speed(value)),
+            Self::Pressure => units.value(Pressure(value)),
+            Self::Length => units.value(Length(value)),
+            Self::PrecipitationRate => units.value(PrecipitationRate(value)),
+        }
+    }
+
+    /// The symbol of the chosen unit of the quantity.
+    fn symbol(self, units: &Units) -> &'static str {
+        match self {
+            Self::Temperature => Temperature::unit(units).symbol(),
+            Self::Speed => Speed::unit(units).symbol(),
+            Self::Pressure => Pressure::unit(units).symbol(),
+            Self::Length => Length::unit(units).symbol(),
+            Self::PrecipitationRate => PrecipitationRate::unit(units).symbol(),
+        }
+    }
+}
+
+/// Normalises a serialised part of the WeatherKit model for output, converting quantities to
+/// the chosen units and times to the display timezone.
+pub fn normalise(value: &mut Value, units: &Units, timezone: Tz) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                match field {
+                    Value::Number(number) => {
+                        if let Some((quantity, value)) = Field::of(name).zip(number.as_f64()) {
+                            *field = json!(quantity.convert(value, units));
+                        }
+                    }
+                    Value::String(text) => {
+                        if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+                            *text = time.with_timezone(&timezone).to_rfc3339();
+                        }
+                    }
+                    _ => normalise(field, units, timezone),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                normalise(item, units, timezone);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Describes the chosen units, so that consumers of the output know what the numbers mean.
+pub fn unit_symbols(units: &Units) -> Value {
+    json!({
+        "temperature": Field::Temperature.symbol(units),
+        "speed": Field::Speed.symbol(units),
+        "pressure": Field::Pressure.symbol(units),
+        "precipitation": Field::Length.symbol(units),
+        "precipitationRate": Field::PrecipitationRate.symbol(units),
+    })
+}
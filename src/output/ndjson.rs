@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::output::series::Series;
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+
+/// Writes a series as newline-delimited JSON, with one object per row keyed by the headers.
+pub fn write(series: &Series) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    for row in &series.rows {
+        let object: Map<String, Value> = series
+            .headers
+            .iter()
+            .cloned()
+            .zip(row.iter().cloned())
+            .collect();
+        serde_json::to_writer(&mut stdout, &object)?;
+        writeln!(stdout)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::cli::Forecast;
+use crate::output::{normalise, Field};
+use crate::units::Units;
+use crate::weatherkit::Weather;
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use inflector::Inflector;
+use serde::Serialize;
+use serde_json::Value;
+
+/// The fields of the current weather that are exported, in order. The first field of each
+/// series is the time its rows are for.
+const CURRENT_FIELDS: &[&str] = &[
+    "asOf",
+    "conditionCode",
+    "daylight",
+    "temperature",
+    "temperatureApparent",
+    "temperatureDewPoint",
+    "humidity",
+    "precipitationIntensity",
+    "pressure",
+    "pressureTrend",
+    "cloudCover",
+    "visibility",
+    "uvIndex",
+    "windDirection",
+    "windSpeed",
+    "windGust",
+];
+
+/// The fields of an hour of the hourly forecast that are exported, in order.
+const HOUR_FIELDS: &[&str] = &[
+    "forecastStart",
+    "conditionCode",
+    "daylight",
+    "temperature",
+    "temperatureApparent",
+    "temperatureDewPoint",
+    "humidity",
+    "precipitationChance",
+    "precipitationType",
+    "precipitationAmount",
+    "snowfallIntensity",
+    "pressure",
+    "pressureTrend",
+    "cloudCover",
+    "visibility",
+    "uvIndex",
+    "windDirection",
+    "windSpeed",
+    "windGust",
+];
+
+/// The fields of a day of the daily forecast that are exported, in order.
+const DAY_FIELDS: &[&str] = &[
+    "forecastStart",
+    "forecastEnd",
+    "conditionCode",
+    "temperatureMax",
+    "temperatureMin",
+    "precipitationChance",
+    "precipitationType",
+    "precipitationAmount",
+    "snowfallAmount",
+    "maxUvIndex",
+    "sunrise",
+    "sunset",
+    "moonPhase",
+    "moonrise",
+    "moonset",
+];
+
+/// The fields of a minute of the next hour forecast that are exported, in order.
+const MINUTE_FIELDS: &[&str] = &["startTime", "precipitationChance", "precipitationIntensity"];
+
+/// A column of a series.
+#[derive(Clone, Debug)]
+struct Column {
+    /// The name of the field in WeatherKit's responses.
+    field: &'static str,
+    /// The stable name of the column, used to select it.
+    name: String,
+}
+
+impl Column {
+    fn new(field: &'static str) -> Self {
+        Self {
+            field,
+            name: field.to_snake_case(),
+        }
+    }
+
+    /// The header of the column, which is its name suffixed with the unit of its values.
+    fn header(&self, units: &Units) -> String {
+        let suffix = match Field::of(self.field) {
+            Some(quantity) => quantity
+                .symbol(units)
+                .to_lowercase()
+                .replace('º', "")
+                .replace('/', "_"),
+            None => match self.field {
+                "visibility" => "m".to_owned(),
+                "windDirection" => "deg".to_owned(),
+                _ => return self.name.clone(),
+            },
+        };
+
+        format!("{}_{}", self.name, suffix)
+    }
+}
+
+/// A forecast as a table of rows, one per hour, day or minute, or a single row for the current
+/// weather.
+pub struct Series {
+    /// The name of the series, e.g. `hourly`.
+    pub name: &'static str,
+    /// The times the rows are for.
+    pub times: Vec<DateTime<Utc>>,
+    /// The headers of the selected columns.
+    pub headers: Vec<String>,
+    /// The values of the selected columns, with quantities converted to the chosen units and
+    /// times to the display timezone.
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Series {
+    /// Builds the series of the given forecast, which must be the current weather or one with a
+    /// series of hours, days or minutes. All columns are included if none are selected.
+    pub fn new(
+        weather: Weather,
+        forecast: Forecast,
+        columns: &[String],
+        units: &Units,
+        timezone: Tz,
+        hours: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        let missing = || {
+            anyhow!(
+                "The response does not include {}.",
+                forecast.datasets()[0].fmt()
+            )
+        };
+        let (name, fields, rows) = match forecast {
+            Forecast::Current => (
+                "current",
+                CURRENT_FIELDS,
+                to_values(&[weather.current_weather.ok_or_else(missing)?])?,
+            ),
+            Forecast::Hourly => {
+                let mut forecast_hours = weather.forecast_hourly.ok_or_else(missing)?.hours;
+                if let Some(hours) = hours {
+                    forecast_hours.truncate(hours);
+                }
+                ("hourly", HOUR_FIELDS, to_values(&forecast_hours)?)
+            }
+            Forecast::Weekly => (
+                "daily",
+                DAY_FIELDS,
+                to_values(&weather.forecast_daily.ok_or_else(missing)?.days)?,
+            ),
+            Forecast::NextHour => (
+                "next_hour",
+                MINUTE_FIELDS,
+                to_values(&weather.forecast_next_hour.ok_or_else(missing)?.minutes)?,
+            ),
+            Forecast::Alerts | Forecast::Calendar => bail!(
+                "Only the current, hourly, weekly and next-hour forecasts can be written as a \
+                 series."
+            ),
+        };
+
+        let columns = select(fields, columns)?;
+        let times = rows
+            .iter()
+            .map(|row| {
+                row.get(fields[0])
+                    .and_then(Value::as_str)
+                    .and_then(|x| DateTime::parse_from_rfc3339(x).ok())
+                    .map(|x| x.with_timezone(&Utc))
+                    .ok_or_else(|| anyhow!("A row of the {} series has no time", name))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            name,
+            times,
+            headers: columns.iter().map(|x| x.header(units)).collect(),
+            rows: rows
+                .into_iter()
+                .map(|mut row| {
+                    normalise(&mut row, units, timezone);
+                    columns
+                        .iter()
+                        .map(|column| row.get(column.field).cloned().unwrap_or_default())
+                        .collect()
+                })
+                .collect(),
+        })
+    }
+}
+
+fn to_values<T: Serialize>(items: &[T]) -> anyhow::Result<Vec<Value>> {
+    items
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<_, _>>()
+        .context("Could not serialise the weather")
+}
+
+/// Picks the columns with the given names, in the given order.
+fn select(fields: &[&'static str], names: &[String]) -> anyhow::Result<Vec<Column>> {
+    let columns: Vec<_> = fields.iter().map(|x| Column::new(x)).collect();
+    if names.is_empty() {
+        return Ok(columns);
+    }
+
+    names
+        .iter()
+        .map(|name| {
+            columns
+                .iter()
+                .find(|x| &x.name == name)
+                .cloned()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "There is no column named \"{}\". Columns are: {}",
+                        name,
+                        columns
+                            .iter()
+                            .map(|x| x.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::UnitSystem;
+    use crate::weatherkit::fixture;
+    use serde_json::json;
+
+    fn series(forecast: Forecast, columns: &[&str], system: UnitSystem) -> anyhow::Result<Series> {
+        Series::new(
+            fixture(),
+            forecast,
+            &columns.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+            &Units::from(system),
+            chrono_tz::Europe::Berlin,
+            None,
+        )
+    }
+
+    #[test]
+    fn suffixes_headers_with_units() {
+        let metric = series(Forecast::Hourly, &[], UnitSystem::Metric).unwrap();
+
+        assert_eq!(metric.name, "hourly");
+        assert_eq!(
+            metric.headers,
+            [
+                "forecast_start",
+                "condition_code",
+                "daylight",
+                "temperature_c",
+                "temperature_apparent_c",
+                "temperature_dew_point_c",
+                "humidity",
+                "precipitation_chance",
+                "precipitation_type",
+                "precipitation_amount_mm",
+                "snowfall_intensity_mm_h",
+                "pressure_hpa",
+                "pressure_trend",
+                "cloud_cover",
+                "visibility_m",
+                "uv_index",
+                "wind_direction_deg",
+                "wind_speed_kph",
+                "wind_gust_kph",
+            ]
+        );
+    }
+
+    #[test]
+    fn selects_and_converts_columns() {
+        let imperial = series(
+            Forecast::Hourly,
+            &["temperature", "forecast_start", "wind_speed"],
+            UnitSystem::Imperial,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imperial.headers,
+            ["temperature_f", "forecast_start", "wind_speed_mph"]
+        );
+        assert_eq!(imperial.times.len(), 3);
+        assert_eq!(imperial.rows[0][0], json!(46.4));
+        assert_eq!(imperial.rows[0][1], json!("2026-10-17T08:00:00+02:00"));
+    }
+
+    #[test]
+    fn rejects_unknown_columns() {
+        let error = series(
+            Forecast::Current,
+            &["temperature", "temprature"],
+            UnitSystem::Metric,
+        )
+        .err()
+        .unwrap()
+        .to_string();
+
+        assert!(error.starts_with("There is no column named \"temprature\". Columns are: as_of,"));
+    }
+
+    #[test]
+    fn rejects_alerts() {
+        assert!(series(Forecast::Alerts, &[], UnitSystem::Metric).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use rand::Rng;
+use std::time::Duration;
+
+/// How transient failures are retried, using exponential backoff with full jitter.
+#[derive(Copy, Clone, Debug)]
+pub struct RetryPolicy {
+    /// The number of times a failed request is retried.
+    pub retries: u32,
+    /// The upper bound of the delay before the first retry, doubled for each further retry.
+    pub initial_backoff: Duration,
+    /// The upper bound of any delay, including one requested by the server.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait before retrying after the given number of failed attempts, or
+    /// `None` if the request shouldn't be retried. A delay requested by the server, e.g. with
+    /// Retry-After, is honoured as long as it doesn't exceed the maximum backoff.
+    pub fn delay(&self, attempts: u32, requested: Option<Duration>) -> Option<Duration> {
+        if attempts > self.retries {
+            return None;
+        }
+
+        match requested {
+            Some(requested) if requested > self.max_backoff => None,
+            Some(requested) => Some(requested),
+            None => {
+                let ceiling = self
+                    .initial_backoff
+                    .saturating_mul(2u32.saturating_pow(attempts - 1))
+                    .min(self.max_backoff);
+                Some(ceiling.mul_f64(rand::thread_rng().gen::<f64>()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: RetryPolicy = RetryPolicy {
+        retries: 5,
+        initial_backoff: Duration::from_millis(500),
+        max_backoff: Duration::from_secs(3),
+    };
+
+    #[test]
+    fn jitters_below_doubling_ceiling() {
+        for (attempts, ceiling) in [(1, 500), (2, 1000), (3, 2000)] {
+            for _ in 0..100 {
+                let delay = POLICY.delay(attempts, None).unwrap();
+                assert!(delay <= Duration::from_millis(ceiling), "{:?}", delay);
+            }
+        }
+    }
+
+    #[test]
+    fn caps_backoff_at_maximum() {
+        for _ in 0..100 {
+            let delay = POLICY.delay(5, None).unwrap();
+            assert!(delay <= POLICY.max_backoff, "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn stops_after_retries() {
+        assert_eq!(POLICY.delay(6, None), None);
+        assert_eq!(POLICY.delay(6, Some(Duration::from_secs(1))), None);
+    }
+
+    #[test]
+    fn honours_requested_delay() {
+        assert_eq!(
+            POLICY.delay(1, Some(Duration::from_secs(2))),
+            Some(Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn gives_up_on_requested_delay_over_maximum() {
+        assert_eq!(POLICY.delay(1, Some(Duration::from_secs(60))), None);
+    }
+}
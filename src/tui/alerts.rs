@@ -1,30 +1,330 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
 use crate::geolocation::Location;
-use crate::weatherkit::WeatherAlertsCollection;
+use crate::i18n::{Catalog, Language, Message};
+use crate::tui::border::{Border, Edge, Separator};
+use crate::tui::text::wrap;
+use crate::weatherkit::{
+    Geometry, Position, Severity, WeatherAlert, WeatherAlertMessage, WeatherAlertSummary,
+    WeatherAlertsCollection,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use console::{measure_text_width, pad_str, style, Alignment};
+
+/// The width of alert cards, which are mostly running text.
+const CARD_WIDTH: usize = 72;
+const TIME_FORMAT: &str = "%H:%M";
 
-struct PreparedWeatherAlertSummary {}
+/// An alert displayed as a bordered card: a title, a list of labelled fields and sections of
+/// running text.
+struct Card {
+    title: String,
+    fields: Vec<(&'static str, String)>,
+    sections: Vec<String>,
+}
+
+struct PreparedWeatherAlertSummary {
+    severity: Severity,
+    card: Card,
+}
 
 pub struct PreparedWeatherAlertSummaries {
     location: String,
     summaries: Vec<PreparedWeatherAlertSummary>,
-    details_url: String,
+    details_url: Option<String>,
     width: usize,
+    catalog: Catalog,
+}
+
+pub struct PreparedWeatherAlert {
+    card: Card,
+}
+
+/// Formats a time in the timezone times are displayed in.
+fn format_time(time: DateTime<Utc>, timezone: Tz, catalog: Catalog) -> String {
+    let local = time.with_timezone(&timezone);
+
+    format!(
+        "{} {} {}",
+        catalog.short_date(local.date_naive()),
+        local.format(TIME_FORMAT),
+        local.format("%Z")
+    )
+}
+
+/// Summarises the area affected by an alert as the number of polygons and their bounding box.
+fn summarise_area(geometries: &[&Geometry]) -> Option<String> {
+    let mut polygons = 0;
+    let mut positions: Vec<&Position> = Vec::new();
+
+    for geometry in geometries {
+        match geometry {
+            Geometry::Polygon { coordinates } => {
+                polygons += 1;
+                positions.extend(coordinates.iter().flatten());
+            }
+            Geometry::MultiPolygon { coordinates } => {
+                polygons += coordinates.len();
+                positions.extend(coordinates.iter().flatten().flatten());
+            }
+            Geometry::Other => {}
+        }
+    }
+
+    let (min_lon, min_lat, max_lon, max_lat) = positions.iter().fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(min_lon, min_lat, max_lon, max_lat), [lon, lat]| {
+            (
+                min_lon.min(*lon),
+                min_lat.min(*lat),
+                max_lon.max(*lon),
+                max_lat.max(*lat),
+            )
+        },
+    );
+
+    (!positions.is_empty()).then(|| {
+        format!(
+            "{} ({:.2}, {:.2} – {:.2}, {:.2})",
+            polygons, min_lat, min_lon, max_lat, max_lon
+        )
+    })
+}
+
+impl WeatherAlertSummary {
+    /// The fields alert summaries and details have in common.
+    pub(crate) fn fields(&self, timezone: Tz, catalog: Catalog) -> Vec<(Message, Option<String>)> {
+        let mut fields = vec![
+            (Message::Source, Some(self.source.clone())),
+            (Message::Area, self.area_name.clone()),
+            (
+                Message::Severity,
+                Some(catalog.severity(self.severity).to_owned()),
+            ),
+            (
+                Message::Urgency,
+                self.urgency.map(|x| catalog.urgency(x).to_owned()),
+            ),
+            (
+                Message::Certainty,
+                Some(catalog.certainty(self.certainty).to_owned()),
+            ),
+        ];
+
+        for (message, time) in [
+            (Message::Issued, Some(self.issued_time)),
+            (Message::Effective, Some(self.effective_time)),
+            (Message::Onset, self.event_onset_time),
+            (Message::Ends, self.event_end_time),
+            (Message::Expires, Some(self.expire_time)),
+        ] {
+            fields.push((message, time.map(|x| format_time(x, timezone, catalog))));
+        }
+
+        if !self.responses.is_empty() {
+            let responses = self
+                .responses
+                .iter()
+                .map(|x| catalog.response(*x))
+                .collect::<Vec<_>>();
+            fields.push((Message::Responses, Some(responses.join(", "))));
+        }
+
+        fields
+    }
+
+    /// The link to more information about the alert, as a section of a card.
+    fn details_section(&self, catalog: Catalog) -> Option<String> {
+        self.details_url
+            .as_ref()
+            .map(|x| format!("{}: {}", catalog.get(Message::MoreInformation), x))
+    }
+
+    fn prepare(self, timezone: Tz, catalog: Catalog) -> PreparedWeatherAlertSummary {
+        let fields = self.fields(timezone, catalog);
+        let sections = self.details_section(catalog).into_iter().collect();
+
+        PreparedWeatherAlertSummary {
+            severity: self.severity,
+            card: Card::new(self.description, fields, sections, catalog),
+        }
+    }
 }
 
 impl WeatherAlertsCollection {
-    pub fn prepare(self, location: &Location) -> PreparedWeatherAlertSummaries {
-        let summaries = Vec::new();
-        let details_url = self.details_url.unwrap_or("".to_owned());
-        let width = 0;
+    pub fn prepare(
+        self,
+        location: &Location,
+        timezone: Tz,
+        catalog: Catalog,
+    ) -> PreparedWeatherAlertSummaries {
+        let mut summaries = self
+            .alerts
+            .into_iter()
+            .map(|x| x.prepare(timezone, catalog))
+            .collect::<Vec<_>>();
+
+        // The most severe alerts come first, and the sort is stable so WeatherKit's order is
+        // kept otherwise
+        summaries.sort_by_key(|x| x.severity);
 
         PreparedWeatherAlertSummaries {
             location: location.to_string(),
             summaries,
-            details_url,
-            width,
+            details_url: self.details_url,
+            width: CARD_WIDTH,
+            catalog,
         }
     }
 }
 
 impl PreparedWeatherAlertSummaries {
-    pub fn render(self) {}
+    pub fn render(self) {
+        if self.summaries.is_empty() {
+            println!("{} {}", self.catalog.get(Message::NoAlerts), self.location);
+            return;
+        }
+
+        for summary in &self.summaries {
+            summary.card.render(self.width);
+        }
+
+        if let Some(details_url) = &self.details_url {
+            println!(
+                "{}: {}",
+                self.catalog.get(Message::MoreInformation),
+                details_url
+            );
+        }
+    }
+}
+
+impl WeatherAlert {
+    pub fn prepare(self, timezone: Tz, language: &Language) -> PreparedWeatherAlert {
+        let catalog = language.catalog();
+
+        let mut fields = self.summary.fields(timezone, catalog);
+        if !self.phenomena.is_empty() {
+            fields.push((Message::Phenomena, Some(self.phenomena.join(", "))));
+        }
+
+        let geometries = self
+            .area
+            .iter()
+            .flat_map(|x| &x.features)
+            .filter_map(|x| x.geometry.as_ref())
+            .collect::<Vec<_>>();
+        fields.push((Message::Polygons, summarise_area(&geometries)));
+
+        // Prefer the messages in the requested language, if the agency issued any
+        let primary_language = language.tag().split('-').next().unwrap_or_default();
+        let has_language = |x: &&WeatherAlertMessage| {
+            x.language
+                .as_deref()
+                .and_then(|x| x.split(['-', '_']).next())
+                .is_some_and(|x| x.eq_ignore_ascii_case(primary_language))
+        };
+        let mut sections = if self.messages.iter().any(|x| has_language(&x)) {
+            self.messages
+                .iter()
+                .filter(has_language)
+                .map(|x| x.text.clone())
+                .collect()
+        } else {
+            self.messages
+                .into_iter()
+                .map(|x| x.text)
+                .collect::<Vec<_>>()
+        };
+        sections.extend(self.summary.details_section(catalog));
+
+        PreparedWeatherAlert {
+            card: Card::new(self.summary.description, fields, sections, catalog),
+        }
+    }
+}
+
+impl PreparedWeatherAlert {
+    pub fn render(self) {
+        self.card.render(CARD_WIDTH);
+    }
+}
+
+impl Card {
+    fn new(
+        title: String,
+        fields: Vec<(Message, Option<String>)>,
+        sections: Vec<String>,
+        catalog: Catalog,
+    ) -> Self {
+        Self {
+            title,
+            fields: fields
+                .into_iter()
+                .filter_map(|(message, value)| Some((catalog.get(message), value?)))
+                .collect(),
+            sections,
+        }
+    }
+
+    fn render(&self, term_width: usize) {
+        let text_width = term_width - 2;
+        let label_width = self
+            .fields
+            .iter()
+            .map(|(label, _)| measure_text_width(label))
+            .max()
+            .unwrap_or_default()
+            + 2;
+
+        let print_line = |line: &str| {
+            println!(
+                "{} {} {}",
+                Border::Left.fmt(),
+                pad_str(line, text_width, Alignment::Left, None),
+                Border::Right.fmt()
+            );
+        };
+
+        // Border top
+        println!("{}", Edge::Top.fmt(term_width));
+
+        // Title
+        for line in wrap(&self.title, text_width) {
+            println!(
+                "{} {} {}",
+                Border::Left.fmt(),
+                style(pad_str(&line, text_width, Alignment::Left, None)).bold(),
+                Border::Right.fmt()
+            );
+        }
+
+        // Separator
+        println!("{}", Separator::Single.fmt(term_width));
+
+        for (label, value) in &self.fields {
+            let label = format!("{}:", label);
+            for (i, line) in wrap(value, text_width - label_width).iter().enumerate() {
+                let label = if i == 0 { label.as_str() } else { "" };
+                print_line(&format!(
+                    "{}{}",
+                    pad_str(label, label_width, Alignment::Left, None),
+                    line
+                ));
+            }
+        }
+
+        for section in &self.sections {
+            // Separator
+            println!("{}", Separator::Dashed.fmt(term_width));
+
+            for line in wrap(section, text_width) {
+                print_line(&line);
+            }
+        }
+
+        // Border bottom
+        println!("{}", Edge::Bottom.fmt(term_width));
+    }
 }
@@ -1,13 +1,15 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::geolocation::Location;
+use crate::i18n::{Catalog, Message};
 use crate::tui::border::{Border, Edge, Separator};
 use crate::tui::dimension::{Dimensions, MIN_CELL_WIDTH, MIN_WIDTH};
 use crate::tui::weather::WindDirection;
+use crate::units::{Pressure, Speed, Temperature, Units};
 use crate::weatherkit::CurrentWeather;
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use console::style;
-use inflector::Inflector;
 
 const TIME_FORMAT: &str = "%H:%M";
 
@@ -16,7 +18,7 @@ pub struct PreparedCurrent {
     location: String,
     condition_code: String,
     temperature: String,
-    apparent_temperature: String,
+    feels_like: String,
     humidity: String,
     dew_point: String,
     wind: String,
@@ -32,11 +34,26 @@ impl CurrentWeather {
         location: &Location,
         sunrise: &DateTime<Utc>,
         sunset: &DateTime<Utc>,
+        timezone: Tz,
+        units: &Units,
+        catalog: Catalog,
     ) -> anyhow::Result<PreparedCurrent> {
-        let temperature = format!("{:.1}ºC", self.temperature);
-        let apparent_temperature = format!("{:.1}ºC", self.temperature_apparent);
-        let humidity = format!("Humidity: {}%", self.humidity * 100.0);
-        let dew_point = format!("Dew point: {:.1}ºC", self.temperature_dew_point);
+        let temperature = units.format(Temperature(self.temperature));
+        let feels_like = format!(
+            "{} {}",
+            catalog.get(Message::FeelsLike),
+            units.format(Temperature(self.temperature_apparent))
+        );
+        let humidity = format!(
+            "{}: {}%",
+            catalog.get(Message::Humidity),
+            self.humidity * 100.0
+        );
+        let dew_point = format!(
+            "{}: {}",
+            catalog.get(Message::DewPoint),
+            units.format(Temperature(self.temperature_dew_point))
+        );
         let maybe_wind_direction = match self.wind_direction {
             Some(wd) => Some(WindDirection::get_direction(wd)?),
             None => None,
@@ -44,23 +61,23 @@ impl CurrentWeather {
 
         let wind = match maybe_wind_direction {
             Some(wind_direction) => format!(
-                "{} {:.1}kph {}",
+                "{} {} {}",
                 wind_direction.get_icon(),
-                self.wind_speed,
-                wind_direction
+                units.format(Speed(self.wind_speed)),
+                wind_direction.abbreviation(catalog)
             ),
 
-            None => format!("{:.1}km/h", self.wind_speed),
+            None => units.format(Speed(self.wind_speed)),
         };
 
-        let pressure = format!("{}hPa", self.pressure);
+        let pressure = units.format(Pressure(self.pressure));
 
-        let sunrise = format!("{}", sunrise.format(TIME_FORMAT));
+        let sunrise = format!("{}", sunrise.with_timezone(&timezone).format(TIME_FORMAT));
 
-        let sunset = format!("{}", sunset.format(TIME_FORMAT));
+        let sunset = format!("{}", sunset.with_timezone(&timezone).format(TIME_FORMAT));
 
         let title_padding = 2 * 2;
-        let longest_cell_width = humidity.len();
+        let longest_cell_width = humidity.chars().count();
         let term_width = MIN_WIDTH + title_padding;
         let cell_width = if longest_cell_width > MIN_CELL_WIDTH {
             longest_cell_width
@@ -72,9 +89,9 @@ impl CurrentWeather {
 
         Ok(PreparedCurrent {
             location: location.to_string(),
-            condition_code: self.condition_code.to_title_case(),
+            condition_code: catalog.condition(&self.condition_code),
             temperature,
-            apparent_temperature,
+            feels_like,
             humidity,
             dew_point,
             wind,
@@ -118,7 +135,7 @@ impl PreparedCurrent {
         println!(
             "{} {: <width$} {}",
             Border::Left.fmt(),
-            format!("Feels like {:.1}ºC", self.apparent_temperature),
+            self.feels_like,
             Border::Right.fmt(),
             width = term_width - 2
         );
@@ -1,25 +1,192 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
 use crate::geolocation::Location;
-use crate::weatherkit::HourlyForecast;
+use crate::i18n::{Catalog, Message};
+use crate::tui::border::{Border, Edge, Separator};
+use crate::tui::weather::WindDirection;
+use crate::units::{Length, Speed, Temperature, Units};
+use crate::weatherkit::{HourWeatherConditions, HourlyForecast};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use console::style;
+
+const TIME_FORMAT: &str = "%H:%M";
+/// The space between the columns of the table.
+const COLUMN_GAP: usize = 2;
+const COLUMNS: [Message; 7] = [
+    Message::Time,
+    Message::Condition,
+    Message::Temperature,
+    Message::Precipitation,
+    Message::Wind,
+    Message::UvIndex,
+    Message::Humidity,
+];
 
-struct PreparedHourlySummary {}
+struct PreparedHourlySummary {
+    date: NaiveDate,
+    cells: [String; COLUMNS.len()],
+}
 
 pub struct PreparedHourlySummeries {
     location: String,
+    header: [String; COLUMNS.len()],
     summaries: Vec<PreparedHourlySummary>,
+    column_widths: [usize; COLUMNS.len()],
     width: usize,
+    catalog: Catalog,
+}
+
+impl HourWeatherConditions {
+    fn prepare(
+        &self,
+        timezone: Tz,
+        units: &Units,
+        catalog: Catalog,
+    ) -> anyhow::Result<PreparedHourlySummary> {
+        let start = self.forecast_start.with_timezone(&timezone);
+
+        let temperature = format!(
+            "{}/{}",
+            units.format(Temperature(self.temperature)),
+            units.format(Temperature(self.temperature_apparent))
+        );
+
+        let precipitation = match self.precipitation_amount {
+            Some(amount) => format!(
+                "{:.0}% {}",
+                self.precipitation_chance * 100.0,
+                units.format(Length(amount))
+            ),
+            None => format!("{:.0}%", self.precipitation_chance * 100.0),
+        };
+
+        let mut wind = match self.wind_direction {
+            Some(wd) => {
+                let wind_direction = WindDirection::get_direction(wd)?;
+                format!(
+                    "{} {} {}",
+                    wind_direction.get_icon(),
+                    units.format(Speed(self.wind_speed)),
+                    wind_direction.abbreviation(catalog)
+                )
+            }
+            None => units.format(Speed(self.wind_speed)),
+        };
+        if let Some(gust) = self.wind_gust {
+            wind.push_str(&format!(" ({})", units.format(Speed(gust))));
+        }
+
+        Ok(PreparedHourlySummary {
+            date: start.date_naive(),
+            cells: [
+                start.format(TIME_FORMAT).to_string(),
+                catalog.condition(&self.condition_code),
+                temperature,
+                precipitation,
+                wind,
+                self.uv_index.to_string(),
+                format!("{:.0}%", self.humidity * 100.0),
+            ],
+        })
+    }
 }
 
 impl HourlyForecast {
-    pub fn prepare(self, location: &Location) -> PreparedHourlySummeries {
-        let summaries = Vec::new();
-        PreparedHourlySummeries {
+    /// Prepares the table of the first `hours` hours of the forecast, or of all of them.
+    pub fn prepare(
+        self,
+        location: &Location,
+        timezone: Tz,
+        hours: Option<usize>,
+        units: &Units,
+        catalog: Catalog,
+    ) -> anyhow::Result<PreparedHourlySummeries> {
+        let summaries = self
+            .hours
+            .iter()
+            .take(hours.unwrap_or(usize::MAX))
+            .map(|x| x.prepare(timezone, units, catalog))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let header = COLUMNS.map(|x| catalog.get(x).to_owned());
+        let mut column_widths = header.each_ref().map(|x| x.chars().count());
+        for summary in &summaries {
+            for (width, cell) in column_widths.iter_mut().zip(&summary.cells) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let width = column_widths.iter().sum::<usize>() + COLUMN_GAP * (COLUMNS.len() - 1) + 2;
+
+        Ok(PreparedHourlySummeries {
             location: location.to_string(),
+            header,
             summaries,
-            width: 0,
-        }
+            column_widths,
+            width,
+            catalog,
+        })
     }
 }
 
 impl PreparedHourlySummeries {
-    pub fn render(self) {}
+    fn row(&self, cells: &[String]) -> String {
+        cells
+            .iter()
+            .zip(self.column_widths)
+            .map(|(cell, width)| format!("{: <width$}", cell))
+            .collect::<Vec<_>>()
+            .join(&" ".repeat(COLUMN_GAP))
+    }
+
+    pub fn render(self) {
+        let term_width = self.width;
+        let print_line = |line: String| {
+            println!(
+                "{} {: <width$} {}",
+                Border::Left.fmt(),
+                line,
+                Border::Right.fmt(),
+                width = term_width - 2
+            );
+        };
+
+        // Border top
+        println!("{}", Edge::Top.fmt(term_width));
+
+        // Location
+        println!(
+            "{} {: ^width$} {}",
+            Border::Left.fmt(),
+            style(&self.location).bold(),
+            Border::Right.fmt(),
+            width = term_width - 2
+        );
+
+        // Header
+        println!("{}", Separator::Single.fmt(term_width));
+        print_line(self.row(&self.header));
+
+        let mut date = None;
+        for summary in &self.summaries {
+            // Start every day with its date
+            if date != Some(summary.date) {
+                date = Some(summary.date);
+                println!("{}", Separator::Dashed.fmt(term_width));
+                println!(
+                    "{} {: <width$} {}",
+                    Border::Left.fmt(),
+                    style(self.catalog.short_date(summary.date)).bold(),
+                    Border::Right.fmt(),
+                    width = term_width - 2
+                );
+            }
+
+            print_line(self.row(&summary.cells));
+        }
+
+        // Border bottom
+        println!("{}", Edge::Bottom.fmt(term_width));
+    }
 }
@@ -7,5 +7,6 @@ pub mod current;
 mod dimension;
 mod hourly;
 mod next_hour;
+mod text;
 mod weather;
 pub mod weekly;
@@ -1,36 +1,260 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
 use crate::geolocation::Location;
-use crate::weatherkit::NextHourForecast;
+use crate::i18n::{Catalog, Message, PrecipitationIntensity};
+use crate::tui::border::{Border, Edge, Separator};
+use crate::tui::text::wrap;
+use crate::units::{PrecipitationRate, Units};
+use crate::weatherkit::{
+    ForecastMinute, ForecastPeriodSummary, NextHourForecast, PrecipitationType,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use console::{measure_text_width, style};
 
-struct PreparedNextHourSummary {}
-
-impl PreparedNextHourSummary {
-    fn render(&self) -> String {
-        "".to_owned()
-    }
-}
+const TIME_FORMAT: &str = "%H:%M";
+/// The number of minutes in the chart, one per column.
+const CHART_MINUTES: usize = 60;
+/// How often the time is labelled under the chart, in minutes.
+const AXIS_INTERVAL: usize = 15;
+/// The upper bounds of the precipitation intensity of each row of the chart, from the bottom, in
+/// millimetres per hour. The rows are light, moderate and heavy precipitation.
+const ROW_INTENSITIES: [f64; 3] = [2.5, 7.6, 15.0];
+/// The characters partially filling a cell of the chart, in eighths.
+const BAR_CHARACTERS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// The chances of precipitation from which each character is used to shade the chance row.
+const CHANCE_CHARACTERS: [(f64, char); 3] = [(0.7, '▓'), (0.4, '▒'), (0.1, '░')];
+/// The chance of precipitation from which bars are highlighted.
+const LIKELY_CHANCE: f64 = 0.5;
 
 pub struct PreparedNextHourSummaries {
     location: String,
-    summaries: Vec<PreparedNextHourSummary>,
+    sentence: String,
+    chart: Vec<String>,
+    chance: String,
+    axis: String,
+    legend: Vec<String>,
     width: usize,
 }
 
+/// Draws one row of the chart, given the range of intensities it covers.
+fn chart_row(minutes: &[ForecastMinute], lower: f64, upper: f64) -> String {
+    minutes
+        .iter()
+        .map(|minute| {
+            let fill = ((minute.precipitation_intensity - lower) / (upper - lower)).clamp(0.0, 1.0);
+            let mut eighths = (fill * 8.0).round() as usize;
+
+            // Make even the slightest precipitation visible
+            if lower == 0.0 && eighths == 0 && minute.precipitation_intensity > 0.0 {
+                eighths = 1;
+            }
+
+            let bar = BAR_CHARACTERS[eighths];
+            if minute.precipitation_chance >= LIKELY_CHANCE {
+                style(bar).cyan().to_string()
+            } else {
+                style(bar).dim().to_string()
+            }
+        })
+        .collect()
+}
+
+/// Describes the precipitation over the next hour, e.g. "Light rain starting in 12 min,
+/// stopping at 14:35".
+fn describe(
+    summary: &[ForecastPeriodSummary],
+    start: DateTime<Utc>,
+    timezone: Tz,
+    catalog: Catalog,
+) -> String {
+    let Some(period) = summary
+        .iter()
+        .find(|x| x.condition != PrecipitationType::Clear)
+    else {
+        return catalog.get(Message::NoPrecipitation).to_owned();
+    };
+
+    let mut sentence = catalog.precipitation(
+        PrecipitationIntensity::from_rate(period.precipitation_intensity),
+        period.condition,
+    );
+
+    let minutes = (period.start_time - start).num_minutes();
+    if minutes > 0 {
+        sentence.push_str(&format!(
+            " {} {} {}",
+            catalog.get(Message::StartingIn),
+            minutes,
+            catalog.get(Message::Minutes)
+        ));
+    }
+
+    match period.end_time {
+        Some(end_time) => {
+            if minutes > 0 {
+                sentence.push(',');
+            }
+            sentence.push_str(&format!(
+                " {} {}",
+                catalog.get(Message::StoppingAt),
+                end_time.with_timezone(&timezone).format(TIME_FORMAT)
+            ));
+        }
+        None if minutes <= 0 => {
+            sentence.push_str(&format!(" {}", catalog.get(Message::ForTheNextHour)));
+        }
+        None => {}
+    }
+
+    sentence
+}
+
 impl NextHourForecast {
-    pub fn prepare(self, location: &Location) -> PreparedNextHourSummaries {
-        let summaries = Vec::new();
-        let width = summaries.len();
-        PreparedNextHourSummaries {
+    pub fn prepare(
+        self,
+        location: &Location,
+        timezone: Tz,
+        units: &Units,
+        catalog: Catalog,
+    ) -> anyhow::Result<PreparedNextHourSummaries> {
+        let minutes = &self.minutes[..self.minutes.len().min(CHART_MINUTES)];
+        let start = minutes
+            .first()
+            .map(|x| x.start_time)
+            .or(self.forecast_start)
+            .unwrap_or_else(Utc::now);
+
+        let sentence = describe(&self.summary, start, timezone, catalog);
+
+        let mut lower = 0.0;
+        let mut chart = Vec::new();
+        for upper in ROW_INTENSITIES {
+            chart.push(chart_row(minutes, lower, upper));
+            lower = upper;
+        }
+        // The heaviest precipitation is drawn at the top
+        chart.reverse();
+
+        let chance = minutes
+            .iter()
+            .map(|minute| {
+                CHANCE_CHARACTERS
+                    .iter()
+                    .find(|(chance, _)| minute.precipitation_chance >= *chance)
+                    .map_or(' ', |(_, character)| *character)
+            })
+            .collect();
+
+        let mut axis = String::new();
+        for minute in minutes.iter().step_by(AXIS_INTERVAL) {
+            let label = minute
+                .start_time
+                .with_timezone(&timezone)
+                .format(TIME_FORMAT)
+                .to_string();
+            axis.push_str(&format!("{: <width$}", label, width = AXIS_INTERVAL));
+        }
+
+        let mut legend = vec![format!(
+            "{}: {}",
+            catalog.get(Message::Chance),
+            CHANCE_CHARACTERS
+                .iter()
+                .rev()
+                .map(|(chance, character)| format!("{} ≥{:.0}%", character, chance * 100.0))
+                .collect::<Vec<_>>()
+                .join("  ")
+        )];
+
+        let peak = minutes
+            .iter()
+            .map(|x| x.precipitation_intensity)
+            .fold(0.0, f64::max);
+        if peak > 0.0 {
+            legend.push(format!(
+                "{}: {}",
+                catalog.get(Message::Peak),
+                units.format(PrecipitationRate(peak))
+            ));
+        }
+
+        // The box fits the chart, unless a translation of the legend is wider
+        let width = legend
+            .iter()
+            .map(|x| measure_text_width(x))
+            .fold(CHART_MINUTES, usize::max)
+            + 2;
+
+        Ok(PreparedNextHourSummaries {
             location: location.to_string(),
-            summaries,
+            sentence,
+            chart,
+            chance,
+            axis,
+            legend,
             width,
-        }
+        })
     }
 }
 
 impl PreparedNextHourSummaries {
     pub fn render(self) {
-        while let Some(summary) = self.summaries.iter().next() {
-            summary.render();
+        let term_width = self.width;
+        let print_line = |line: &str| {
+            println!(
+                "{} {: <width$} {}",
+                Border::Left.fmt(),
+                line,
+                Border::Right.fmt(),
+                width = term_width - 2
+            );
+        };
+
+        // Border top
+        println!("{}", Edge::Top.fmt(term_width));
+
+        // Location
+        println!(
+            "{} {: ^width$} {}",
+            Border::Left.fmt(),
+            style(&self.location).bold(),
+            Border::Right.fmt(),
+            width = term_width - 2
+        );
+
+        // Separator
+        println!("{}", Separator::Single.fmt(term_width));
+
+        // Summary
+        for line in wrap(&self.sentence, term_width - 2) {
+            print_line(&line);
+        }
+
+        // Blank line
+        println!("{}", Separator::Blank.fmt(term_width));
+
+        // Chart, which is padded already as it contains escape codes
+        for row in &self.chart {
+            let padding = " ".repeat((term_width - 2).saturating_sub(measure_text_width(row)));
+            println!(
+                "{} {}{} {}",
+                Border::Left.fmt(),
+                row,
+                padding,
+                Border::Right.fmt()
+            );
         }
+        print_line(&self.chance);
+        print_line(&self.axis);
+
+        // Legend
+        println!("{}", Separator::Dashed.fmt(term_width));
+        for line in &self.legend {
+            print_line(line);
+        }
+
+        // Border bottom
+        println!("{}", Edge::Bottom.fmt(term_width));
     }
 }
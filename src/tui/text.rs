@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use console::measure_text_width;
+
+/// Wraps text into lines at most `width` columns wide, breaking between words where possible.
+/// Line breaks in the text are kept, so paragraphs stay apart. Widths are measured as displayed
+/// in a terminal, so wide characters such as CJK take up two columns.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+
+        for word in paragraph.split_whitespace() {
+            let mut word = word.to_owned();
+            let mut word_width = measure_text_width(&word);
+
+            if line_width > 0 && line_width + 1 + word_width > width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+
+            // Words longer than a whole line, e.g. URLs, have to be broken up
+            while word_width > width {
+                if line_width > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+
+                let (head, tail) = split_at_width(&word, width);
+                lines.push(head.to_owned());
+                word = tail.to_owned();
+                word_width = measure_text_width(&word);
+            }
+
+            if line_width > 0 {
+                line.push(' ');
+                line_width += 1;
+            }
+            line.push_str(&word);
+            line_width += word_width;
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Splits text after as many characters as fit in `width` columns, but at least one so that
+/// wrapping always makes progress.
+fn split_at_width(text: &str, width: usize) -> (&str, &str) {
+    let mut split = 0;
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        if split > 0 && measure_text_width(&text[..end]) > width {
+            break;
+        }
+        split = end;
+    }
+
+    text.split_at(split)
+}
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
+use crate::i18n::Catalog;
 use anyhow::anyhow;
-use std::fmt::{Display, Formatter};
 
 #[derive(Eq, PartialEq, Debug)]
 pub enum WindDirection {
@@ -47,19 +47,21 @@ impl WindDirection {
             Self::NorthWest => '↘',
         }
     }
-}
 
-impl Display for WindDirection {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            WindDirection::NorthWest => write!(f, "NW"),
-            WindDirection::North => write!(f, "N"),
-            WindDirection::NorthEast => write!(f, "NE"),
-            WindDirection::East => write!(f, "E"),
-            WindDirection::SouthEast => write!(f, "SW"),
-            WindDirection::South => write!(f, "S"),
-            WindDirection::SouthWest => write!(f, "SW"),
-            WindDirection::West => write!(f, "W"),
+    /// The abbreviation of the direction in the given language, e.g. `NE`.
+    pub const fn abbreviation(&self, catalog: Catalog) -> &'static str {
+        match (catalog, self) {
+            (_, Self::North) => "N",
+            (Catalog::English, Self::NorthEast) => "NE",
+            (Catalog::English, Self::East) => "E",
+            (Catalog::English, Self::SouthEast) => "SE",
+            (Catalog::German, Self::NorthEast) => "NO",
+            (Catalog::German, Self::East) => "O",
+            (Catalog::German, Self::SouthEast) => "SO",
+            (_, Self::South) => "S",
+            (_, Self::SouthWest) => "SW",
+            (_, Self::West) => "W",
+            (_, Self::NorthWest) => "NW",
         }
     }
 }
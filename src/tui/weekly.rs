@@ -1,10 +1,12 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use crate::i18n::Catalog;
 use crate::tui::border::{Border, Edge, Separator};
 use crate::tui::dimension::MIN_CELL_WIDTH;
+use crate::units::{Temperature, Units};
 use crate::weatherkit::{DailyForecast, DayWeatherConditions};
 use anyhow::anyhow;
-use inflector::Inflector;
+use chrono_tz::Tz;
 use std::cmp;
 
 struct PreparedDailySummary {
@@ -16,45 +18,65 @@ struct PreparedDailySummary {
 pub struct PreparedDailySummaries {
     summaries: Vec<PreparedDailySummary>,
     width: usize,
+    cell_width: usize,
 }
 
 impl DayWeatherConditions {
-    fn prepare(&self) -> PreparedDailySummary {
-        let date = format!("{}", self.forecast_start.format("%a, %h %d"));
+    fn prepare(&self, timezone: Tz, units: &Units, catalog: Catalog) -> PreparedDailySummary {
+        let date = catalog.short_date(self.forecast_start.with_timezone(&timezone).date_naive());
         let temperature = format!(
-            "{:.1}ºC/{:.1}ºC",
-            self.temperature_max, self.temperature_min
+            "{}/{}",
+            units.format(Temperature(self.temperature_max)),
+            units.format(Temperature(self.temperature_min))
         );
 
         PreparedDailySummary {
             date,
             temperature,
-            condition_code: self.condition_code.to_title_case(),
+            condition_code: catalog.condition(&self.condition_code),
         }
     }
 }
 
 impl DailyForecast {
-    pub fn prepare(self) -> anyhow::Result<PreparedDailySummaries> {
-        let summaries = self.days.iter().map(|x| x.prepare()).collect::<Vec<_>>();
+    /// Prepares the summaries of each day. Days are dated in the location's own timezone, as
+    /// that's where they start and end.
+    pub fn prepare(
+        self,
+        timezone: Tz,
+        units: &Units,
+        catalog: Catalog,
+    ) -> anyhow::Result<PreparedDailySummaries> {
+        let summaries = self
+            .days
+            .iter()
+            .map(|x| x.prepare(timezone, units, catalog))
+            .collect::<Vec<_>>();
+        let cell_width = summaries
+            .iter()
+            .map(|x| x.date.chars().count() + 2)
+            .fold(MIN_CELL_WIDTH, cmp::max);
         let width = summaries
             .iter()
-            .map(|x| x.condition_code.len() + x.temperature.len() + x.condition_code.len())
+            .map(|x| cell_width + x.temperature.chars().count() + x.condition_code.chars().count())
             .max()
             .ok_or_else(|| {
                 anyhow!("Internal error: could not get maximum of prepared daily summaries.")
             })?
-            + 10;
+            + 5;
 
-        Ok(PreparedDailySummaries { summaries, width })
+        Ok(PreparedDailySummaries {
+            summaries,
+            width,
+            cell_width,
+        })
     }
 }
 
 impl PreparedDailySummary {
     fn render(&self, term_width: usize, cell_width: usize) -> String {
-        let width = term_width - cmp::max(11, self.date.len()) - self.temperature.len() - 5
-            + cell_width
-            - MIN_CELL_WIDTH;
+        // Right-align the condition code against the border
+        let width = term_width - 2 - cell_width - self.temperature.chars().count() - 1;
 
         format!(
             "{: <cell_width$}{} {: >width$}",
@@ -66,7 +88,7 @@ impl PreparedDailySummary {
 impl PreparedDailySummaries {
     pub fn render(self) {
         let term_width = self.width;
-        let cell_width = MIN_CELL_WIDTH;
+        let cell_width = self.cell_width;
 
         // Border top
         println!("{}", Edge::Top.fmt(term_width));
@@ -0,0 +1,453 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::cli::UnitArgs;
+use crate::config::UnitsConfig;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// A unit a quantity can be displayed in.
+pub trait Unit: Copy {
+    /// The symbol appended to values, e.g. `ºC`.
+    fn symbol(self) -> &'static str;
+
+    /// The number of decimal places values are displayed with.
+    fn precision(self) -> usize;
+}
+
+/// A physical quantity as reported by WeatherKit, in metric units.
+pub trait Quantity {
+    type Unit: Unit;
+
+    /// The unit the user chose to display the quantity in.
+    fn unit(units: &Units) -> Self::Unit;
+
+    /// The value of the quantity in the given unit.
+    fn convert(&self, unit: Self::Unit) -> f64;
+}
+
+/// A temperature, in degrees Celsius.
+#[derive(Copy, Clone, Debug)]
+pub struct Temperature(pub f64);
+
+/// A speed, in kilometres per hour.
+#[derive(Copy, Clone, Debug)]
+pub struct Speed(pub f64);
+
+/// An air pressure, in hectopascals (millibars).
+#[derive(Copy, Clone, Debug)]
+pub struct Pressure(pub f64);
+
+/// A length such as an amount of precipitation, in millimetres.
+#[derive(Copy, Clone, Debug)]
+pub struct Length(pub f64);
+
+/// A precipitation rate, in millimetres per hour.
+#[derive(Copy, Clone, Debug)]
+pub struct PrecipitationRate(pub f64);
+
+#[derive(Copy, Clone, Eq, PartialEq, ValueEnum, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, ValueEnum, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SpeedUnit {
+    /// Kilometres per hour
+    #[value(alias = "km/h")]
+    #[serde(alias = "km/h")]
+    Kph,
+    /// Miles per hour
+    Mph,
+    /// Metres per second
+    #[value(name = "m/s")]
+    #[serde(rename = "m/s")]
+    MetresPerSecond,
+    /// Nautical miles per hour
+    Knots,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, ValueEnum, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PressureUnit {
+    /// Hectopascals, which are the same as millibars
+    #[value(alias = "mbar")]
+    #[serde(alias = "mbar", alias = "hPa")]
+    Hpa,
+    /// Inches of mercury
+    #[serde(alias = "inHg")]
+    Inhg,
+    /// Millimetres of mercury
+    #[serde(alias = "mmHg")]
+    Mmhg,
+    /// Kilopascals
+    #[serde(alias = "kPa")]
+    Kpa,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, ValueEnum, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    /// Millimetres
+    Mm,
+    /// Inches
+    In,
+}
+
+/// The presets of units, which individual quantities can be overridden on top of.
+#[derive(Copy, Clone, Eq, PartialEq, Default, ValueEnum, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    /// ºC, km/h, hPa and mm
+    #[default]
+    Metric,
+    /// ºF, mph, inHg and inches
+    Imperial,
+    /// ºC, mph, hPa and mm, as commonly used in the UK
+    Uk,
+}
+
+/// The units to display each quantity in.
+#[derive(Copy, Clone, Debug)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    pub speed: SpeedUnit,
+    pub pressure: PressureUnit,
+    pub length: LengthUnit,
+}
+
+impl From<UnitSystem> for Units {
+    fn from(value: UnitSystem) -> Self {
+        match value {
+            UnitSystem::Metric => Self {
+                temperature: TemperatureUnit::Celsius,
+                speed: SpeedUnit::Kph,
+                pressure: PressureUnit::Hpa,
+                length: LengthUnit::Mm,
+            },
+            UnitSystem::Imperial => Self {
+                temperature: TemperatureUnit::Fahrenheit,
+                speed: SpeedUnit::Mph,
+                pressure: PressureUnit::Inhg,
+                length: LengthUnit::In,
+            },
+            UnitSystem::Uk => Self {
+                temperature: TemperatureUnit::Celsius,
+                speed: SpeedUnit::Mph,
+                pressure: PressureUnit::Hpa,
+                length: LengthUnit::Mm,
+            },
+        }
+    }
+}
+
+impl Units {
+    /// Picks the units from the command line, falling back to the config file and then to the
+    /// preset either of them selects.
+    pub fn resolve(args: &UnitArgs, config: &UnitsConfig) -> Self {
+        let preset = Self::from(args.units.unwrap_or(config.system));
+
+        Self {
+            temperature: args
+                .temperature
+                .or(config.temperature)
+                .unwrap_or(preset.temperature),
+            speed: args.wind.or(config.wind).unwrap_or(preset.speed),
+            pressure: args.pressure.or(config.pressure).unwrap_or(preset.pressure),
+            length: args
+                .precipitation
+                .or(config.precipitation)
+                .unwrap_or(preset.length),
+        }
+    }
+
+    /// The value of a quantity in the chosen unit.
+    pub fn value<Q: Quantity>(&self, quantity: Q) -> f64 {
+        quantity.convert(Q::unit(self))
+    }
+
+    /// Formats a quantity in the chosen unit, e.g. `12.3ºC`.
+    pub fn format<Q: Quantity>(&self, quantity: Q) -> String {
+        let unit = Q::unit(self);
+
+        format!(
+            "{:.*}{}",
+            unit.precision(),
+            quantity.convert(unit),
+            unit.symbol()
+        )
+    }
+}
+
+impl Unit for TemperatureUnit {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Celsius => "ºC",
+            Self::Fahrenheit => "ºF",
+            Self::Kelvin => "K",
+        }
+    }
+
+    fn precision(self) -> usize {
+        1
+    }
+}
+
+impl Unit for SpeedUnit {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Kph => "kph",
+            Self::Mph => "mph",
+            Self::MetresPerSecond => "m/s",
+            Self::Knots => "kn",
+        }
+    }
+
+    fn precision(self) -> usize {
+        1
+    }
+}
+
+impl Unit for PressureUnit {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Hpa => "hPa",
+            Self::Inhg => "inHg",
+            Self::Mmhg => "mmHg",
+            Self::Kpa => "kPa",
+        }
+    }
+
+    fn precision(self) -> usize {
+        match self {
+            Self::Hpa | Self::Mmhg => 0,
+            Self::Kpa => 1,
+            Self::Inhg => 2,
+        }
+    }
+}
+
+impl Unit for LengthUnit {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Mm => "mm",
+            Self::In => "in",
+        }
+    }
+
+    fn precision(self) -> usize {
+        match self {
+            Self::Mm => 1,
+            Self::In => 2,
+        }
+    }
+}
+
+/// The unit of a precipitation rate, which is the unit of precipitation amounts per hour.
+#[derive(Copy, Clone, Debug)]
+pub struct PrecipitationRateUnit(LengthUnit);
+
+impl Unit for PrecipitationRateUnit {
+    fn symbol(self) -> &'static str {
+        match self.0 {
+            LengthUnit::Mm => "mm/h",
+            LengthUnit::In => "in/h",
+        }
+    }
+
+    fn precision(self) -> usize {
+        self.0.precision()
+    }
+}
+
+impl Quantity for Temperature {
+    type Unit = TemperatureUnit;
+
+    fn unit(units: &Units) -> Self::Unit {
+        units.temperature
+    }
+
+    fn convert(&self, unit: Self::Unit) -> f64 {
+        match unit {
+            TemperatureUnit::Celsius => self.0,
+            TemperatureUnit::Fahrenheit => self.0 * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Kelvin => self.0 + 273.15,
+        }
+    }
+}
+
+impl Quantity for Speed {
+    type Unit = SpeedUnit;
+
+    fn unit(units: &Units) -> Self::Unit {
+        units.speed
+    }
+
+    fn convert(&self, unit: Self::Unit) -> f64 {
+        match unit {
+            SpeedUnit::Kph => self.0,
+            SpeedUnit::Mph => self.0 / 1.609344,
+            SpeedUnit::MetresPerSecond => self.0 / 3.6,
+            SpeedUnit::Knots => self.0 / 1.852,
+        }
+    }
+}
+
+impl Quantity for Pressure {
+    type Unit = PressureUnit;
+
+    fn unit(units: &Units) -> Self::Unit {
+        units.pressure
+    }
+
+    fn convert(&self, unit: Self::Unit) -> f64 {
+        match unit {
+            PressureUnit::Hpa => self.0,
+            PressureUnit::Inhg => self.0 * 0.029_529_983,
+            PressureUnit::Mmhg => self.0 * 0.750_061_68,
+            PressureUnit::Kpa => self.0 / 10.0,
+        }
+    }
+}
+
+impl Quantity for Length {
+    type Unit = LengthUnit;
+
+    fn unit(units: &Units) -> Self::Unit {
+        units.length
+    }
+
+    fn convert(&self, unit: Self::Unit) -> f64 {
+        match unit {
+            LengthUnit::Mm => self.0,
+            LengthUnit::In => self.0 / 25.4,
+        }
+    }
+}
+
+impl Quantity for PrecipitationRate {
+    type Unit = PrecipitationRateUnit;
+
+    fn unit(units: &Units) -> Self::Unit {
+        PrecipitationRateUnit(units.length)
+    }
+
+    fn convert(&self, unit: Self::Unit) -> f64 {
+        Length(self.0).convert(unit.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    fn args() -> UnitArgs {
+        UnitArgs {
+            units: None,
+            temperature: None,
+            wind: None,
+            pressure: None,
+            precipitation: None,
+        }
+    }
+
+    #[test]
+    fn converts_temperatures() {
+        assert_close(
+            Temperature(100.0).convert(TemperatureUnit::Fahrenheit),
+            212.0,
+        );
+        assert_close(
+            Temperature(-40.0).convert(TemperatureUnit::Fahrenheit),
+            -40.0,
+        );
+        assert_close(Temperature(0.0).convert(TemperatureUnit::Kelvin), 273.15);
+    }
+
+    #[test]
+    fn converts_speeds() {
+        assert_close(Speed(1.609344).convert(SpeedUnit::Mph), 1.0);
+        assert_close(Speed(1.852).convert(SpeedUnit::Knots), 1.0);
+        assert_close(Speed(36.0).convert(SpeedUnit::MetresPerSecond), 10.0);
+    }
+
+    #[test]
+    fn converts_pressures() {
+        assert_close(Pressure(1013.25).convert(PressureUnit::Mmhg), 760.0);
+        assert!((Pressure(1013.25).convert(PressureUnit::Inhg) - 29.92).abs() < 0.005);
+        assert_close(Pressure(1013.25).convert(PressureUnit::Kpa), 101.325);
+    }
+
+    #[test]
+    fn converts_lengths_and_rates() {
+        assert_close(Length(25.4).convert(LengthUnit::In), 1.0);
+        assert_close(
+            PrecipitationRate(12.7).convert(PrecipitationRateUnit(LengthUnit::In)),
+            0.5,
+        );
+    }
+
+    #[test]
+    fn formats_with_symbol_and_precision() {
+        let units = Units::from(UnitSystem::Imperial);
+        assert_eq!(units.format(Temperature(20.0)), "68.0ºF");
+        assert_eq!(units.format(Pressure(1013.25)), "29.92inHg");
+        assert_eq!(units.format(Length(10.0)), "0.39in");
+        assert_eq!(units.format(PrecipitationRate(2.54)), "0.10in/h");
+    }
+
+    #[test]
+    fn resolves_presets() {
+        let uk = Units::resolve(
+            &args(),
+            &UnitsConfig {
+                system: UnitSystem::Uk,
+                ..Default::default()
+            },
+        );
+        assert_eq!(uk.temperature, TemperatureUnit::Celsius);
+        assert_eq!(uk.speed, SpeedUnit::Mph);
+        assert_eq!(uk.pressure, PressureUnit::Hpa);
+        assert_eq!(uk.length, LengthUnit::Mm);
+
+        let metric = Units::resolve(&args(), &UnitsConfig::default());
+        assert_eq!(metric.speed, SpeedUnit::Kph);
+    }
+
+    #[test]
+    fn prefers_command_line_over_config_over_preset() {
+        let config = UnitsConfig {
+            system: UnitSystem::Metric,
+            temperature: Some(TemperatureUnit::Kelvin),
+            wind: Some(SpeedUnit::Knots),
+            ..Default::default()
+        };
+        let args = UnitArgs {
+            units: Some(UnitSystem::Imperial),
+            wind: Some(SpeedUnit::MetresPerSecond),
+            ..args()
+        };
+
+        let units = Units::resolve(&args, &config);
+
+        // Set on the command line
+        assert_eq!(units.speed, SpeedUnit::MetresPerSecond);
+        // Set in the config file, which beats the preset from the command line
+        assert_eq!(units.temperature, TemperatureUnit::Kelvin);
+        // From the command line's preset rather than the config file's
+        assert_eq!(units.pressure, PressureUnit::Inhg);
+        assert_eq!(units.length, LengthUnit::In);
+    }
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 pub const WEATHERKIT_API_BASE_URL: &str = "https://weatherkit.apple.com/api/v1";
 
 /// The system of units that the weather data is reported in.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum UnitsSystem {
     /// Metric
@@ -14,7 +14,7 @@ pub enum UnitsSystem {
 }
 
 /// Descriptive information about the weather data.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Metadata {
     /// The URL of the legal attribution for the data source.
@@ -45,7 +45,7 @@ pub struct Metadata {
 }
 
 /// The direction of change of the sea level air pressure.
-#[derive(Eq, PartialEq, Deserialize, Debug)]
+#[derive(Eq, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum PressureTrend {
     /// The sea level air pressure is increasing.
@@ -56,7 +56,7 @@ pub enum PressureTrend {
     Steady,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CurrentWeather {
     /// Descriptive information about the weather data.
@@ -96,7 +96,7 @@ pub struct CurrentWeather {
 }
 
 /// The type of precipitation forecasted to occur during the day.
-#[derive(Eq, PartialEq, Deserialize, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum PrecipitationType {
     /// No precipitation is occurring.
@@ -116,7 +116,7 @@ pub enum PrecipitationType {
 }
 
 /// THe shape of the moon as seen by the observer on the ground at a given time.
-#[derive(Eq, PartialEq, Deserialize, Debug)]
+#[derive(Eq, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum MoonPhase {
     /// The moon isn't visible.
@@ -138,7 +138,7 @@ pub enum MoonPhase {
 }
 
 /// A summary forecast for a daytime or overnight period.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DayPartForecast {
     /// The percentage of the sky covered with clouds during the period, from 0 to
@@ -166,7 +166,7 @@ pub struct DayPartForecast {
 }
 
 /// The historical or forecasted weather conditions for a specified day.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DayWeatherConditions {
     /// An enumeration value indicating the condition at the time.
@@ -222,7 +222,7 @@ pub struct DayWeatherConditions {
 }
 
 /// A collection of day forecasts for a specified range of days.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DailyForecast {
     /// Descriptive information about the weather data.
@@ -234,7 +234,7 @@ pub struct DailyForecast {
 }
 
 /// The historical or forecasted weather conditions for a specified hour.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct HourWeatherConditions {
     /// The percentage of the sky covered with clouds during the period, from 0 to 1.
@@ -278,7 +278,7 @@ pub struct HourWeatherConditions {
 }
 
 /// A collection of hour forecasts for a specified range of hours.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct HourlyForecast {
     /// Descriptive information about the weather data.
@@ -288,7 +288,7 @@ pub struct HourlyForecast {
 }
 
 /// The precipitation forecast for a specified minute.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ForecastMinute {
     /// The probability of precipitation during this minute.
@@ -300,7 +300,7 @@ pub struct ForecastMinute {
 }
 
 /// The summary for a specified period in the minute forecast.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ForecastPeriodSummary {
     /// The type of precipitation forecasted.
@@ -316,7 +316,7 @@ pub struct ForecastPeriodSummary {
 }
 
 /// The next hour forecast information.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct NextHourForecast {
     /// Descriptive information about the weather data.
@@ -332,7 +332,7 @@ pub struct NextHourForecast {
 }
 
 /// How likely the event is to occur.
-#[derive(Deserialize, Debug)]
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum Certainty {
     /// The event has already occurred or is ongoing.
@@ -348,7 +348,7 @@ pub enum Certainty {
 }
 
 /// The recommended action from a reporting agency.
-#[derive(Deserialize, Debug)]
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum ResponseType {
     /// Take shelter in place.
@@ -371,8 +371,8 @@ pub enum ResponseType {
     None,
 }
 
-/// The level of danger to life and property.
-#[derive(Deserialize, Debug)]
+/// The level of danger to life and property, ordered from the most to the least severe.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum Severity {
     /// Extraordinary threat.
@@ -388,7 +388,7 @@ pub enum Severity {
 }
 
 /// An indication of urgency of action from the reporting agency.
-#[derive(Deserialize, Debug)]
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum Urgency {
     /// Take responsive action immediately.
@@ -404,7 +404,7 @@ pub enum Urgency {
 }
 
 /// Detailed information about the weather alert.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct WeatherAlertSummary {
     /// An official designation of the affected area.
@@ -442,7 +442,7 @@ pub struct WeatherAlertSummary {
 }
 
 /// A collecton of weather alerts.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct WeatherAlertsCollection {
     /// An array of weather alert summaries.
@@ -451,8 +451,74 @@ pub struct WeatherAlertsCollection {
     pub details_url: Option<String>,
 }
 
+/// The text of a weather alert in one language.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherAlertMessage {
+    /// The ISO language code of the text.
+    pub language: Option<String>,
+    /// The text of the alert as issued by the reporting agency.
+    pub text: String,
+}
+
+/// A point as a longitude and latitude pair.
+pub type Position = [f64; 2];
+
+/// The shape of an area affected by a weather alert.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    /// A polygon, as a list of linear rings of which the first is the outline and the rest are
+    /// holes.
+    Polygon { coordinates: Vec<Vec<Position>> },
+    /// A list of polygons.
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<Position>>>,
+    },
+    /// Any other kind of geometry, which wetter doesn't display.
+    #[serde(other)]
+    Other,
+}
+
+/// A part of the area affected by a weather alert.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherAlertAreaFeature {
+    /// The shape of the part of the area.
+    pub geometry: Option<Geometry>,
+}
+
+/// The area affected by a weather alert, as a GeoJSON feature collection.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherAlertArea {
+    /// The parts of the area.
+    #[serde(default)]
+    pub features: Vec<WeatherAlertAreaFeature>,
+}
+
+/// The full details of a weather alert.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherAlert {
+    /// The information also included in the summary of the alert.
+    #[serde(flatten)]
+    pub summary: WeatherAlertSummary,
+    /// The area affected by the event.
+    pub area: Option<WeatherAlertArea>,
+    /// The URL of the legal attribution for the data source.
+    #[serde(rename = "attributionURL")]
+    pub attribution_url: Option<String>,
+    /// The text of the alert as issued by the reporting agency, possibly in several languages.
+    #[serde(default)]
+    pub messages: Vec<WeatherAlertMessage>,
+    /// The kinds of weather the alert is about, e.g. `wind` or `flood`.
+    #[serde(default)]
+    pub phenomena: Vec<String>,
+}
+
 /// The collection of all requested weather data.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Weather {
     /// The current weather for the requested location.
@@ -468,7 +534,7 @@ pub struct Weather {
 }
 
 /// The collection of weather information for a location.
-#[derive(Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum DataSet {
     /// The current weather for the requested location.
@@ -494,3 +560,31 @@ impl DataSet {
         }
     }
 }
+
+impl Weather {
+    /// Returns the earliest time any of the included datasets expires, if any of them has one.
+    pub fn expire_time(&self) -> Option<DateTime<Utc>> {
+        [
+            self.current_weather
+                .as_ref()
+                .map(|x| x.metadata.expire_time),
+            self.forecast_daily.as_ref().map(|x| x.metadata.expire_time),
+            self.forecast_hourly
+                .as_ref()
+                .map(|x| x.metadata.expire_time),
+            self.forecast_next_hour
+                .as_ref()
+                .map(|x| x.metadata.expire_time),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+}
+
+/// A response including every dataset, for testing what is made of it.
+#[cfg(test)]
+pub fn fixture() -> Weather {
+    serde_json::from_str(include_str!("../tests/fixtures/weather.json"))
+        .expect("The fixture should be a valid response")
+}
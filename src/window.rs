@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::cli::{Forecast, TimeArgs};
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// The formats accepted for times without a UTC offset, which are taken to be local to the
+/// location.
+const LOCAL_DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+const LOCAL_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The format WeatherKit expects times in.
+const QUERY_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+/// A compact format for times in cache entry names.
+const CACHE_NAME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// The period to request weather for, when it isn't the present.
+#[derive(Default, Debug)]
+pub struct TimeWindow {
+    /// The time to get the current weather as of.
+    pub current_as_of: Option<DateTime<Utc>>,
+    /// The start of the daily forecast.
+    pub daily_start: Option<DateTime<Utc>>,
+    /// The end of the daily forecast.
+    pub daily_end: Option<DateTime<Utc>>,
+    /// The start of the hourly forecast.
+    pub hourly_start: Option<DateTime<Utc>>,
+    /// The end of the hourly forecast.
+    pub hourly_end: Option<DateTime<Utc>>,
+}
+
+/// Parses a time given on the command line. Dates stand for the start of the day, or the end
+/// of it if `end_of_day` is set, so that `--from 2026-10-01 --to 2026-10-05` spans five days.
+fn parse_time(value: &str, timezone: Tz, end_of_day: bool) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let local = LOCAL_DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, LOCAL_DATE_FORMAT).ok()?;
+            let date = if end_of_day { date.succ_opt()? } else { date };
+            date.and_hms_opt(0, 0, 0)
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "\"{}\" is not a valid time, expected e.g. 2026-10-16, 2026-10-16T08:00 or \
+                 2026-10-16T08:00:00+02:00",
+                value
+            )
+        })?;
+
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|x| x.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("{} doesn't exist in {}", local, timezone))
+}
+
+impl TimeWindow {
+    /// Builds the window of the given forecast from the command line, interpreting times
+    /// without a UTC offset in the location's timezone.
+    pub fn from_args(args: &TimeArgs, forecast: Forecast, timezone: Tz) -> anyhow::Result<Self> {
+        let from = args
+            .from
+            .as_deref()
+            .map(|x| parse_time(x, timezone, false))
+            .transpose()
+            .context("Invalid --from")?;
+        let to = args
+            .to
+            .as_deref()
+            .map(|x| parse_time(x, timezone, true))
+            .transpose()
+            .context("Invalid --to")?;
+        let at = args
+            .at
+            .as_deref()
+            .map(|x| parse_time(x, timezone, false))
+            .transpose()
+            .context("Invalid --at")?;
+
+        if let (Some(from), Some(to)) = (from, to) {
+            if to <= from {
+                return Err(anyhow!("--to must be after --from"));
+            }
+        }
+
+        // The current weather is displayed alongside the sunrise and sunset of the same day,
+        // so the daily forecast has to cover it unless a window was asked for
+        let (daily_start, daily_end) = match (forecast, from, to, at) {
+            (Forecast::Current, None, None, Some(at)) => {
+                let midnight = at
+                    .with_timezone(&timezone)
+                    .date_naive()
+                    .and_hms_opt(0, 0, 0)
+                    .and_then(|x| timezone.from_local_datetime(&x).earliest())
+                    .map(|x| x.with_timezone(&Utc));
+                (midnight, midnight.map(|x| x + Duration::days(1)))
+            }
+            _ => (from, to),
+        };
+
+        Ok(Self {
+            current_as_of: at,
+            daily_start,
+            daily_end,
+            hourly_start: from,
+            hourly_end: to,
+        })
+    }
+
+    fn parameters(&self) -> [(&'static str, Option<DateTime<Utc>>); 5] {
+        [
+            ("currentAsOf", self.current_as_of),
+            ("dailyStart", self.daily_start),
+            ("dailyEnd", self.daily_end),
+            ("hourlyStart", self.hourly_start),
+            ("hourlyEnd", self.hourly_end),
+        ]
+    }
+
+    /// The query parameters to send to WeatherKit.
+    pub fn queries(&self) -> Vec<(&'static str, String)> {
+        self.parameters()
+            .into_iter()
+            .filter_map(|(name, time)| Some((name, time?.format(QUERY_FORMAT).to_string())))
+            .collect()
+    }
+
+    /// A string identifying the window in cache entry names.
+    pub fn cache_name(&self) -> String {
+        let times = self
+            .parameters()
+            .map(|(_, time)| match time {
+                Some(time) => time.format(CACHE_NAME_FORMAT).to_string(),
+                None => String::new(),
+            })
+            .join("-");
+
+        if times.chars().all(|x| x == '-') {
+            "now".to_owned()
+        } else {
+            times
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Berlin;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn args(from: Option<&str>, to: Option<&str>, at: Option<&str>) -> TimeArgs {
+        TimeArgs {
+            from: from.map(str::to_owned),
+            to: to.map(str::to_owned),
+            at: at.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn dates_are_local_midnights() {
+        assert_eq!(
+            parse_time("2026-10-05", Berlin, false).unwrap(),
+            utc("2026-10-04T22:00:00Z")
+        );
+        // The end of the day is the next midnight, so that the day is included
+        assert_eq!(
+            parse_time("2026-10-05", Berlin, true).unwrap(),
+            utc("2026-10-05T22:00:00Z")
+        );
+    }
+
+    #[test]
+    fn times_without_offset_are_local() {
+        assert_eq!(
+            parse_time("2026-01-15T08:00", Berlin, false).unwrap(),
+            utc("2026-01-15T07:00:00Z")
+        );
+        assert_eq!(
+            parse_time("2026-07-15 08:00:30", Berlin, true).unwrap(),
+            utc("2026-07-15T06:00:30Z")
+        );
+    }
+
+    #[test]
+    fn times_with_offset_are_kept() {
+        assert_eq!(
+            parse_time("2026-01-15T08:00:00-05:00", Berlin, false).unwrap(),
+            utc("2026-01-15T13:00:00Z")
+        );
+    }
+
+    #[test]
+    fn handles_daylight_saving_transitions() {
+        // Clocks in Berlin skip from 02:00 to 03:00 on 2026-03-29
+        assert!(parse_time("2026-03-29T02:30", Berlin, false).is_err());
+        // and repeat 02:00 to 03:00 on 2026-10-25, of which the earlier is taken
+        assert_eq!(
+            parse_time("2026-10-25T02:30", Berlin, false).unwrap(),
+            utc("2026-10-25T00:30:00Z")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        assert!(parse_time("tomorrow", Berlin, false).is_err());
+        assert!(parse_time("2026-13-01", Berlin, false).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_ranges() {
+        let args = args(Some("2026-10-05"), Some("2026-10-04"), None);
+        assert!(TimeWindow::from_args(&args, Forecast::Weekly, Berlin).is_err());
+    }
+
+    #[test]
+    fn narrows_daily_forecast_to_day_of_current_weather() {
+        let args = args(None, None, Some("2026-10-16T08:00"));
+
+        let current = TimeWindow::from_args(&args, Forecast::Current, Berlin).unwrap();
+        assert_eq!(current.current_as_of, Some(utc("2026-10-16T06:00:00Z")));
+        assert_eq!(current.daily_start, Some(utc("2026-10-15T22:00:00Z")));
+        assert_eq!(current.daily_end, Some(utc("2026-10-16T22:00:00Z")));
+
+        let weekly = TimeWindow::from_args(&args, Forecast::Weekly, Berlin).unwrap();
+        assert_eq!(weekly.daily_start, None);
+        assert_eq!(weekly.daily_end, None);
+    }
+
+    #[test]
+    fn names_cache_entries_by_window() {
+        assert_eq!(TimeWindow::default().cache_name(), "now");
+
+        let args = args(Some("2026-10-01"), Some("2026-10-02"), None);
+        let window = TimeWindow::from_args(&args, Forecast::Hourly, Berlin).unwrap();
+        assert_eq!(
+            window.cache_name(),
+            "-20260930T220000Z-20261002T220000Z-20260930T220000Z-20261002T220000Z"
+        );
+        assert_eq!(
+            window.queries(),
+            [
+                ("dailyStart", "2026-09-30T22:00:00Z".to_owned()),
+                ("dailyEnd", "2026-10-02T22:00:00Z".to_owned()),
+                ("hourlyStart", "2026-09-30T22:00:00Z".to_owned()),
+                ("hourlyEnd", "2026-10-02T22:00:00Z".to_owned()),
+            ]
+        );
+    }
+}